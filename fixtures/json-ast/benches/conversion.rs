@@ -0,0 +1,53 @@
+//! Compares converting a large JSON tree with node kinds checked by id and by name.
+//!
+//! Run with `cargo bench -p json-ast`.
+
+use std::time::{Duration, Instant};
+
+use json_ast::{ids, names};
+
+const ROUNDS: usize = 10;
+
+/// A JSON document of a few megabytes mixing every kind of value.
+fn large_document() -> String {
+    let items: Vec<String> = (0..20_000)
+        .map(|idx| {
+            format!(
+                r#"{{"id": {idx}, "name": "item \"{idx}\"", "ratio": {}.5e-3, "tags": ["a", "b\n", null], "ok": true, "nested": {{"values": [1, 2, [3, {{}}]], "none": false}}}}"#,
+                idx * 7
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(",\n"))
+}
+
+/// Best time of `ROUNDS` runs of `convert`.
+fn best(mut convert: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            convert();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let source = large_document();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let tree = parser.parse(&source, None).unwrap();
+
+    let by_id = best(|| {
+        ids::Document::parse_tree(&tree, source.as_bytes()).unwrap();
+    });
+    let by_name = best(|| {
+        names::Document::parse_tree(&tree, source.as_bytes()).unwrap();
+    });
+
+    println!("converting {} KiB of JSON, best of {ROUNDS}:", source.len() / 1024);
+    println!("  kinds by id:   {by_id:?}");
+    println!("  kinds by name: {by_name:?}");
+    println!("  speedup:       {:.2}x", by_name.as_secs_f64() / by_id.as_secs_f64());
+}
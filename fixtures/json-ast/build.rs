@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use sitter_ast::{Config, GrammarJSON};
+
+/// Generates the code of `grammar` with `config` into `file` of the output directory.
+fn generate(grammar: &str, config: &Config, file: &str) {
+    println!("cargo:rerun-if-changed={grammar}");
+    let grammar: GrammarJSON =
+        serde_json::from_str(&std::fs::read_to_string(grammar).unwrap()).unwrap();
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join(file), grammar.to_source_with(config).unwrap()).unwrap();
+}
+
+fn main() {
+    let config =
+        Config { language: Some("tree_sitter_json::language".to_string()), ..Default::default() };
+    generate("../../tests/grammars/json.json", &config, "json_ids.rs");
+    generate("../../tests/grammars/json.json", &Config::default(), "json_names.rs");
+    generate("shapes.json", &Config::default(), "shapes.rs");
+    generate("root.json", &config, "root.rs");
+}
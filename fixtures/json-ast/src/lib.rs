@@ -0,0 +1,27 @@
+//! The AST of JSON generated from `tests/grammars/json.json`, in both ways node kinds can be
+//! checked, the one of `shapes.json` which parses JSON trees with the shapes the generated
+//! parsers handle and the one of `root.json` whose document wraps a single value.
+
+/// Generated with the language of the grammar, checking node kinds by id.
+#[allow(dead_code)]
+pub mod ids {
+    include!(concat!(env!("OUT_DIR"), "/json_ids.rs"));
+}
+
+/// Generated without the language, checking node kinds by name.
+#[allow(dead_code)]
+pub mod names {
+    include!(concat!(env!("OUT_DIR"), "/json_names.rs"));
+}
+
+/// Sequences, choices, optional and repeated members over the node kinds of JSON.
+#[allow(dead_code)]
+pub mod shapes {
+    include!(concat!(env!("OUT_DIR"), "/shapes.rs"));
+}
+
+/// A document of a single array or number, parsed from strings with the JSON language.
+#[allow(dead_code)]
+pub mod root {
+    include!(concat!(env!("OUT_DIR"), "/root.rs"));
+}
@@ -0,0 +1,217 @@
+use json_ast::{ids, names};
+use sitter_ast_runtime::{ParseError, Span, TSParser};
+
+const SOURCE: &str = r#"{"a": [1, true, null, "x\ny", {}], "b": ""} // comment
+[-2.5e3, false]"#;
+
+fn parse(source: &str) -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    parser.parse(source, None).unwrap()
+}
+
+#[test]
+fn test_convert() {
+    let document = ids::Document::parse_str(SOURCE).unwrap();
+    let [ids::Value::Object(object), ids::Value::Array(array)] = document.value.as_slice() else {
+        panic!("not an object and an array: {document:?}");
+    };
+    assert_eq!((object.pair.len(), array.value.len()), (2, 2));
+
+    let tree = parse(SOURCE);
+    let root = tree.root_node();
+    let object = ids::Object::parse(root.child(0).unwrap(), SOURCE.as_bytes()).unwrap();
+    let pairs = &object.pair;
+    assert_eq!(pairs.len(), 2);
+    let ids::Value::Array(array) = &pairs[0].value else {
+        panic!("not an array: {:?}", pairs[0]);
+    };
+    let kinds: Vec<_> = array
+        .value
+        .iter()
+        .map(|item| format!("{item:?}").split('(').next().unwrap().to_string())
+        .collect();
+    assert_eq!(kinds, ["Number", "True", "Null", "String", "Object"]);
+    let ids::Value::String(string @ ids::String::Token0(span)) = &pairs[1].value else {
+        panic!("not an empty string: {:?}", pairs[1]);
+    };
+    assert_eq!(*span, Span { start: 40, end: 42 });
+    assert_eq!(ids::AstNode::span(string), Some(*span));
+
+    let array = ids::Array::parse(root.child(2).unwrap(), SOURCE.as_bytes()).unwrap();
+    let ids::Value::Number(number) = &array.value[0] else {
+        panic!("not a number: {array:?}");
+    };
+    assert_eq!(number.value, "-2.5e3");
+    assert_eq!(number.span, Span { start: 56, end: 62 });
+}
+
+#[test]
+fn test_kind_names() {
+    let tree = parse(SOURCE);
+
+    let by_id = ids::Document::parse_tree(&tree, SOURCE.as_bytes()).unwrap();
+    let by_name = names::Document::parse_tree(&tree, SOURCE.as_bytes()).unwrap();
+    assert_eq!(format!("{by_id:?}"), format!("{by_name:?}"));
+}
+
+#[test]
+fn test_convert_errors() {
+    let err = ids::Document::parse_str("[1, @]").unwrap_err();
+    assert_eq!(err, ParseError::Syntax { span: Span { start: 2, end: 5 } });
+
+    let tree = parse("[1, 2]");
+    let array = tree.root_node().child(0).unwrap();
+    let err = ids::Object::parse(array, b"[1, 2]").map(drop).unwrap_err();
+    assert_eq!(err.to_string(), "expected `object` but found `array` at 0..6");
+}
+
+#[test]
+fn test_any_node() {
+    let tree = parse(SOURCE);
+    let root = tree.root_node();
+
+    let nodes: Vec<_> = (0..root.child_count())
+        .map(|idx| ids::AnyNode::from_ts_node(root.child(idx).unwrap(), SOURCE.as_bytes()))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let kinds: Vec<_> = nodes
+        .iter()
+        .map(|item| format!("{item:?}").split('(').next().unwrap().to_string())
+        .collect();
+    assert_eq!(kinds, ["Object", "Comment", "Array"]);
+
+    let bracket = root.child(2).unwrap().child(0).unwrap();
+    assert_eq!(ids::NodeKind::of(bracket), None);
+    assert_eq!(ids::NodeKind::of(root), Some(ids::NodeKind::Document));
+    assert_eq!(ids::NodeKind::from_name("pair").map(|kind| kind.as_str()), Some("pair"));
+    let err = ids::AnyNode::from_ts_node(bracket, SOURCE.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected `[` at 55..56");
+}
+
+/// The named nodes under `node` and itself, in depth-first order.
+fn named_nodes<'t>(node: tree_sitter::Node<'t>, res: &mut Vec<tree_sitter::Node<'t>>) {
+    if node.is_named() {
+        res.push(node);
+    }
+    for idx in 0..node.child_count() {
+        named_nodes(node.child(idx).unwrap(), res);
+    }
+}
+
+#[test]
+fn test_any_node_of_every_kind() {
+    let tree = parse(SOURCE);
+    let mut nodes = vec![];
+    named_nodes(tree.root_node(), &mut nodes);
+
+    let mut kinds = vec![];
+    for node in nodes {
+        let any = ids::AnyNode::from_ts_node(node, SOURCE.as_bytes())
+            .unwrap_or_else(|err| panic!("`{}` does not convert: {err}", node.kind()));
+        assert_eq!(any.as_node_ref().kind().map(|kind| kind.as_str()), Some(node.kind()));
+        kinds.push(node.kind());
+    }
+    kinds.sort();
+    kinds.dedup();
+    assert_eq!(
+        kinds,
+        [
+            "array",
+            "comment",
+            "document",
+            "escape_sequence",
+            "false",
+            "null",
+            "number",
+            "object",
+            "pair",
+            "string",
+            "string_content",
+            "true"
+        ]
+    );
+}
+
+/// Kinds of the values under `node` in depth-first order.
+fn walk(node: ids::AnyNodeRef<'_>, res: &mut Vec<ids::NodeKind>) {
+    res.extend(node.kind());
+    for child in node.children() {
+        walk(child, res);
+    }
+}
+
+#[test]
+fn test_ast_node() {
+    use ids::AstNode;
+
+    let document = ids::Document::parse_str(SOURCE).unwrap();
+    assert_eq!(document.kind(), Some(ids::NodeKind::Document));
+    assert_eq!(document.span(), Some(Span { start: 0, end: 70 }));
+    // The object starts and ends with literal tokens, which keep no span of their own.
+    let object = document.children().next().unwrap();
+    assert_eq!(object.span(), Some(Span { start: 0, end: 43 }));
+
+    let mut kinds = vec![];
+    for child in document.children() {
+        walk(child, &mut kinds);
+    }
+    let count = |kind| kinds.iter().filter(|item| **item == kind).count();
+    assert_eq!(kinds[..3], [ids::NodeKind::Object, ids::NodeKind::Pair, ids::NodeKind::String]);
+    assert_eq!(count(ids::NodeKind::Pair), 2);
+    assert_eq!(count(ids::NodeKind::Number), 2);
+
+    let numbers: Vec<_> = document
+        .children()
+        .flat_map(|child| child.children().collect::<Vec<_>>())
+        .filter_map(|child| child.downcast_ref::<ids::Array>())
+        .flat_map(|array| array.children())
+        .filter_map(|child| child.children().next()?.downcast_ref::<ids::Number>())
+        .map(|number| number.value.as_str())
+        .collect();
+    assert_eq!(numbers, ["-2.5e3"]);
+    assert!(document.downcast_ref::<ids::Document>().is_some());
+    assert!(document.downcast_ref::<ids::Array>().is_none());
+}
+
+/// Arrays nested `depth` times around a number.
+fn nested(depth: usize) -> String {
+    format!("{}1{}", "[".repeat(depth), "]".repeat(depth))
+}
+
+#[test]
+fn test_too_deep() {
+    let source = nested(200);
+    let document = ids::Document::parse_str(&source).unwrap();
+    assert!(format!("{document:?}").contains("Number"));
+
+    // Every array nests two types, itself and the value it is.
+    assert!(ids::Document::parse_str(&nested(255)).is_ok());
+    let err = ids::Document::parse_str(&nested(256)).unwrap_err();
+    assert!(matches!(err, ParseError::TooDeep { limit: 512, .. }), "{err}");
+
+    let source = nested(100_000);
+    let err = ids::Document::parse_str(&source).unwrap_err();
+    assert!(matches!(err, ParseError::TooDeep { limit: 512, .. }), "{err}");
+
+    let tree = parse(&nested(10));
+    let err = ids::Document::parse_with_max_depth(tree.root_node(), b"", 5).unwrap_err();
+    assert_eq!(err, ParseError::TooDeep { limit: 5, span: Span { start: 2, end: 19 } });
+}
+
+#[test]
+fn test_drop_nested() {
+    // Converting needs a larger stack than dropping, which must not recurse.
+    let source = nested(20_000);
+    let document = std::thread::Builder::new()
+        .stack_size(1 << 30)
+        .spawn(move || {
+            let tree = parse(&source);
+            ids::Document::parse_with_max_depth(tree.root_node(), source.as_bytes(), usize::MAX)
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+        .unwrap();
+    drop(document);
+}
@@ -0,0 +1,35 @@
+use json_ast::root::{AnyNode, Array, Document, Value};
+use sitter_ast_runtime::Span;
+
+#[test]
+fn test_parse_str() {
+    let document = Document::parse_str(" [12] ").unwrap();
+    let Value::Array(Array { number: Some(number), .. }) = &document.value else {
+        panic!("not an array of a number: {document:?}");
+    };
+    assert_eq!((number.value.as_str(), number.span), ("12", Span { start: 2, end: 4 }));
+
+    let document = Document::parse_str("7").unwrap();
+    assert!(matches!(&document.value, Value::Number(number) if number.value == "7"));
+}
+
+#[test]
+fn test_parse_str_errors() {
+    // The document node of JSON holds any number of values, this one a single one.
+    let err = Document::parse_str("1 2").unwrap_err();
+    assert_eq!(err.to_string(), "unexpected `number` at 2..3");
+
+    let err = Document::parse_str("{}").unwrap_err();
+    assert_eq!(err.to_string(), "expected `Value` but found `object` at 0..2");
+}
+
+#[test]
+fn test_any_node() {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let tree = parser.parse("[1]", None).unwrap();
+
+    // The document wraps its value in a node of its own, like any other named kind.
+    let any = AnyNode::from_ts_node(tree.root_node(), b"[1]").unwrap();
+    assert!(matches!(any, AnyNode::Document(Document { value: Value::Array(_), .. })));
+}
@@ -0,0 +1,125 @@
+use json_ast::shapes::{self, ArrayToken1, ObjectToken1, Value};
+use sitter_ast_runtime::TSParser;
+
+fn parse(source: &str) -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    parser.parse(source, None).unwrap()
+}
+
+/// The value of the single node under the root of `source`.
+fn convert(source: &str) -> Value {
+    let tree = parse(source);
+    let node = tree.root_node().named_child(0).unwrap();
+    match node.kind() {
+        "array" => Value::Array(TSParser::parse(node, source.as_bytes()).unwrap()),
+        "object" => Value::Object(TSParser::parse(node, source.as_bytes()).unwrap()),
+        "string" => Value::String(TSParser::parse(node, source.as_bytes()).unwrap()),
+        kind => panic!("unexpected `{kind}`"),
+    }
+}
+
+#[test]
+fn test_choice_backtracking() {
+    let value = convert("[1, 2]");
+    let Value::Array(array) = &value else { unreachable!() };
+    let Some(ArrayToken1::Token0(first, second)) = &array.array_token_1 else {
+        panic!("not two numbers: {array:?}");
+    };
+    assert_eq!((first.value.as_str(), second.value.as_str()), ("1", "2"));
+
+    // Both sequences start with a number, the first one fails on the string.
+    let value = convert(r#"[1, "x"]"#);
+    let Value::Array(array) = &value else { unreachable!() };
+    let Some(ArrayToken1::Token1(number, string)) = &array.array_token_1 else {
+        panic!("not a number and a string: {array:?}");
+    };
+    assert_eq!(number.value, "1");
+    assert_eq!(string.string_content.as_ref().unwrap().value, "x");
+
+    let value = convert("[true]");
+    let Value::Array(array) = &value else { unreachable!() };
+    let Some(ArrayToken1::Value(value)) = &array.array_token_1 else {
+        panic!("not a single value: {array:?}");
+    };
+    assert!(matches!(**value, Value::True(_)));
+}
+
+#[test]
+fn test_nullable_alternative() {
+    let value = convert("[]");
+    let Value::Array(array) = &value else { unreachable!() };
+    assert!(array.array_token_1.is_none());
+
+    let value = convert("{}");
+    let Value::Object(object) = &value else { unreachable!() };
+    assert_eq!(format!("{:?}", object.object_token_1), "Pairs(Pairs { value: [] })");
+
+    // Two pairs match the sequence, a single one falls back to the repeated pairs.
+    let value = convert(r#"{"a": 1, "b": null}"#);
+    let Value::Object(object) = &value else { unreachable!() };
+    let ObjectToken1::Token0(first, second) = &object.object_token_1 else {
+        panic!("not two pairs: {object:?}");
+    };
+    assert!(matches!(*first.value, Value::Number(_)));
+    assert!(matches!(*second.value, Value::Null(_)));
+
+    let value = convert(r#"{"a": []}"#);
+    let Value::Object(object) = &value else { unreachable!() };
+    let ObjectToken1::Pairs(pairs) = &object.object_token_1 else {
+        panic!("not repeated pairs: {object:?}");
+    };
+    assert!(format!("{pairs:?}").starts_with("Pairs { value: [Pair {"));
+}
+
+#[test]
+fn test_optional_and_repeated() {
+    let value = convert(r#""ab""#);
+    let Value::String(string) = &value else { unreachable!() };
+    assert_eq!(string.string_content.as_ref().unwrap().value, "ab");
+    assert!(string.escape_sequence.is_empty());
+
+    let value = convert(r#""\n\t""#);
+    let Value::String(string) = &value else { unreachable!() };
+    assert!(string.string_content.is_none());
+    let escapes: Vec<_> = string.escape_sequence.iter().map(|item| item.value.as_str()).collect();
+    assert_eq!(escapes, [r"\n", r"\t"]);
+
+    let value = convert(r#""""#);
+    let Value::String(string) = &value else { unreachable!() };
+    assert!(string.string_content.is_none() && string.escape_sequence.is_empty());
+
+    // The content only comes before the escapes.
+    let tree = parse(r#""a\nb""#);
+    let node = tree.root_node().named_child(0).unwrap();
+    let err = shapes::String::parse(node, br#""a\nb""#).unwrap_err();
+    assert_eq!(err.to_string(), "expected `\"` but found `string_content` at 4..5");
+}
+
+#[test]
+fn test_aliased_node() {
+    // `false` nodes are the hidden `_no` rule aliased to their kind.
+    let value = convert("[false]");
+    let Value::Array(array) = &value else { unreachable!() };
+    let Some(ArrayToken1::Value(value)) = &array.array_token_1 else {
+        panic!("not a single value: {array:?}");
+    };
+    assert!(matches!(**value, Value::False(_)));
+
+    let tree = parse("[false]");
+    let node = tree.root_node().named_child(0).unwrap();
+    assert!(shapes::False::parse(node.named_child(0).unwrap(), b"[false]").is_ok());
+    let err = shapes::False::parse(node, b"[false]").map(drop).unwrap_err();
+    assert_eq!(err.to_string(), "expected `false` but found `array` at 0..7");
+}
+
+#[test]
+fn test_document() {
+    let source = "[1, 2] // comment\n{}";
+    let tree = parse(source);
+    let document = shapes::Document::parse_tree(&tree, source.as_bytes()).unwrap();
+    assert!(
+        matches!(document.value.as_slice(), [Value::Array(_), Value::Object(_)]),
+        "{document:?}"
+    );
+}
@@ -0,0 +1,12 @@
+//! The JSON grammar of `tree-sitter-json` 0.24.8, built from its generated `src/parser.c`.
+
+use tree_sitter::Language;
+
+extern "C" {
+    fn tree_sitter_json() -> Language;
+}
+
+/// The tree-sitter [`Language`] of JSON.
+pub fn language() -> Language {
+    unsafe { tree_sitter_json() }
+}
@@ -0,0 +1,275 @@
+use tree_sitter::{Node, TreeCursor};
+
+use crate::{ParseError, ParseResult, Span, TSParser};
+
+/// How deep types may be nested by default, see [`Children::with_max_depth`].
+///
+/// The limit counts types, not nodes: a level of the tree usually nests more than one type, like
+/// the hidden `_value` choice around every JSON array, so JSON arrays may only be nested 255 deep.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// The children of a node but its extras, consumed in order while parsing the node.
+///
+/// Every level of the conversion moves the same [`TreeCursor`]: parsing a node descends into its
+/// children and climbs back once they are consumed, so no cursor is created per node.
+///
+/// Unnamed children which no parsed type asks for, like the separators of a list, are skipped
+/// when looking for the next node of a type.
+///
+/// Parsing a type from the children of a parsing type recurses, so the nesting of the parsed
+/// types is bounded and deeper input fails with [`ParseError::TooDeep`] instead of overflowing
+/// the stack.
+pub struct Children<'c, 't> {
+    cursor: &'c mut TreeCursor<'t>,
+    parent: Node<'t>,
+    /// The child the cursor is on, none once every child is consumed.
+    next: Option<Node<'t>>,
+    /// Whether the cursor moved down to the children of `parent` and has to climb back.
+    entered: bool,
+    /// How many types are being parsed around the next child.
+    depth: usize,
+    max_depth: usize,
+}
+
+/// One way of parsing a type made of alternatives, see [`Children::choose`].
+pub struct Alternative<T> {
+    /// Whether the alternative may start with a node, like [`TSParser::starts_with`].
+    pub starts_with: fn(Node<'_>) -> bool,
+    /// Whether the alternative may match no child at all.
+    pub nullable: bool,
+    pub parse: fn(&mut Children<'_, '_>, &[u8]) -> ParseResult<T>,
+}
+
+impl<'c, 't> Children<'c, 't> {
+    /// The children of the node under `cursor`.
+    pub fn new(cursor: &'c mut TreeCursor<'t>) -> Self {
+        let parent = cursor.node();
+        let entered = cursor.goto_first_child();
+        let next = entered.then(|| cursor.node());
+        let mut res =
+            Self { cursor, parent, next, entered, depth: 0, max_depth: DEFAULT_MAX_DEPTH };
+        res.skip_extras();
+        res
+    }
+
+    /// The node under `cursor` alone, to parse a type from a single node.
+    pub fn root(cursor: &'c mut TreeCursor<'t>) -> Self {
+        let parent = cursor.node();
+        Self {
+            cursor,
+            parent,
+            next: Some(parent),
+            entered: false,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Fails with [`ParseError::TooDeep`] when parsing more than `max_depth` nested types, which
+    /// include the types of hidden rules and nested members as well as those of nodes.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn skip_extras(&mut self) {
+        while self.next.is_some_and(|node| node.is_extra()) {
+            self.next = self.cursor.goto_next_sibling().then(|| self.cursor.node());
+        }
+    }
+
+    /// Moves the cursor back to `next`, a child before the current one.
+    fn restore(&mut self, next: Option<Node<'t>>) {
+        if let Some(node) = next {
+            while self.cursor.node() != node && self.cursor.goto_previous_sibling() {}
+        }
+        self.next = next;
+    }
+
+    /// The node whose children these are.
+    pub fn parent(&self) -> Node<'t> {
+        self.parent
+    }
+
+    /// The next child, without consuming it.
+    pub fn peek(&self) -> Option<Node<'t>> {
+        self.next
+    }
+
+    /// Whether the next child is of one of `kinds`.
+    pub fn next_is(&self, kinds: &[&str]) -> bool {
+        self.next.is_some_and(|node| kinds.contains(&node.kind()))
+    }
+
+    /// Skips the unnamed children before the next one matching `pred`, if there is such a child
+    /// before the next named one.
+    pub fn skip_to(&mut self, pred: impl Fn(Node<'t>) -> bool) -> bool {
+        let next = self.next;
+        while let Some(node) = self.next {
+            if pred(node) {
+                return true;
+            }
+            if node.is_named() {
+                break;
+            }
+            self.next();
+        }
+        self.restore(next);
+        false
+    }
+
+    /// Moves to the next child starting a type, failing if there is none.
+    fn seek(
+        &mut self,
+        expected: &'static str,
+        starts_with: fn(Node<'_>) -> bool,
+    ) -> ParseResult<Node<'t>> {
+        if !self.next.is_some_and(starts_with) {
+            self.skip_to(starts_with);
+        }
+        match self.next {
+            Some(node) if starts_with(node) => Ok(node),
+            Some(node) => Err(ParseError::kind(expected, node)),
+            None => Err(ParseError::missing(expected, self.parent)),
+        }
+    }
+
+    /// Consumes the next child of the `expected` kind without looking at its children, for
+    /// tokens.
+    pub fn leaf(
+        &mut self,
+        expected: &'static str,
+        starts_with: fn(Node<'_>) -> bool,
+    ) -> ParseResult<Node<'t>> {
+        let node = self.seek(expected, starts_with)?;
+        self.next();
+        Ok(node)
+    }
+
+    /// Consumes the next child of the `expected` kind, building it with `body` from the node and
+    /// its own children.
+    pub fn node<T>(
+        &mut self,
+        expected: &'static str,
+        starts_with: fn(Node<'_>) -> bool,
+        body: impl FnOnce(Node<'t>, &mut Children<'_, 't>) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        let node = self.seek(expected, starts_with)?;
+        let (depth, max_depth) = (self.depth, self.max_depth);
+        let mut children = Children::new(self.cursor);
+        children.depth = depth;
+        children.max_depth = max_depth;
+        let res = body(node, &mut children)?;
+        children.finish()?;
+        self.next();
+        Ok(res)
+    }
+
+    /// Parses `T` one level deeper than the types being parsed.
+    fn nested<T: TSParser>(&mut self, source: &[u8]) -> ParseResult<T> {
+        if self.depth >= self.max_depth {
+            let node = self.next.unwrap_or(self.parent);
+            return Err(ParseError::TooDeep { limit: self.max_depth, span: Span::of(node) });
+        }
+        self.depth += 1;
+        let res = T::parse_children(self, source);
+        self.depth -= 1;
+        res
+    }
+
+    /// Parses `T` from the next children.
+    pub fn parse<T: TSParser>(&mut self, source: &[u8]) -> ParseResult<T> {
+        self.nested(source)
+    }
+
+    /// Parses `T` from the next children if it starts with the next one.
+    pub fn parse_if<T: TSParser>(&mut self, source: &[u8]) -> ParseResult<Option<T>> {
+        if !self.next.is_some_and(T::starts_with) {
+            return Ok(None);
+        }
+        self.nested(source).map(Some)
+    }
+
+    /// Parses `T` as long as it starts with the next child, or with the next one after unnamed
+    /// separators.
+    pub fn parse_while<T: TSParser>(&mut self, source: &[u8]) -> ParseResult<Vec<T>> {
+        let mut res = vec![];
+        loop {
+            if !self.next.is_some_and(T::starts_with)
+                && (res.is_empty() || !self.skip_to(T::starts_with))
+            {
+                return Ok(res);
+            }
+            let next = self.next;
+            res.push(self.nested(source)?);
+            if self.next == next {
+                return Ok(res);
+            }
+        }
+    }
+
+    /// Parses the first of `alternatives` matching the next children.
+    ///
+    /// Only the alternatives which may start with the next child are tried, in order, and the
+    /// children consumed by a failed one are restored before trying the following one.
+    pub fn choose<T>(
+        &mut self,
+        alternatives: &[Alternative<T>],
+        expected: &'static str,
+        source: &[u8],
+    ) -> ParseResult<T> {
+        let next = self.next;
+        let mut candidates = alternatives
+            .iter()
+            .filter(|item| item.nullable || next.is_some_and(item.starts_with))
+            .peekable();
+        if candidates.peek().is_none()
+            && self.skip_to(|node| alternatives.iter().any(|item| (item.starts_with)(node)))
+        {
+            return self.choose(alternatives, expected, source);
+        }
+
+        while let Some(item) = candidates.next() {
+            if candidates.peek().is_none() {
+                return (item.parse)(self, source);
+            }
+            match (item.parse)(self, source) {
+                Ok(res) => return Ok(res),
+                Err(_) => self.restore(next),
+            }
+        }
+        Err(match next {
+            Some(node) => ParseError::kind(expected, node),
+            None => ParseError::missing(expected, self.parent),
+        })
+    }
+
+    /// Fails if a named child is left.
+    pub fn finish(mut self) -> ParseResult<()> {
+        match self.find(|node| node.is_named()) {
+            Some(node) => Err(ParseError::unexpected(node)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'t> Iterator for Children<'_, 't> {
+    type Item = Node<'t>;
+
+    fn next(&mut self) -> Option<Node<'t>> {
+        let res = self.next?;
+        self.next = self.cursor.goto_next_sibling().then(|| self.cursor.node());
+        self.skip_extras();
+        Some(res)
+    }
+}
+
+impl Drop for Children<'_, '_> {
+    /// Climbs back to the parent, also when parsing the children failed, so the cursor is where
+    /// the enclosing level expects it.
+    fn drop(&mut self) {
+        if self.entered {
+            self.cursor.goto_parent();
+        }
+    }
+}
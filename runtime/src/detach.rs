@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+
+/// A type whose values may nest each other without bound, like the types of recursive rules.
+///
+/// Dropping such a value field by field recurses as deep as the values are nested, so generated
+/// types implement `Drop` with [`drop_nested`], which moves the nested values out to a stack on
+/// the heap and drops them one at a time.
+pub trait Detach {
+    /// Moves the values nested in `self` which may nest more of them to `stack`.
+    fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>);
+
+    /// A value nesting nothing, left in place of a detached boxed value.
+    fn empty() -> Self
+    where
+        Self: Sized;
+}
+
+impl<T: Detach> Detach for Vec<T> {
+    fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>) {
+        for item in self {
+            item.detach(stack);
+        }
+    }
+
+    fn empty() -> Self {
+        Vec::new()
+    }
+}
+
+thread_local! {
+    /// The values [`drop_nested`] is dropping on this thread, if it is.
+    static STACK: RefCell<Option<Vec<Box<dyn Detach>>>> = const { RefCell::new(None) };
+}
+
+/// Marks [`drop_nested`] as emptying its stack until dropped, even by a panic.
+struct Dropping;
+
+impl Dropping {
+    fn start() -> Self {
+        STACK.set(Some(vec![]));
+        Dropping
+    }
+}
+
+impl Drop for Dropping {
+    fn drop(&mut self) {
+        // The values left by a panic are dropped once the stack is released.
+        let stack = STACK.take();
+        drop(stack);
+    }
+}
+
+/// Drops the values nested in `value` without recursing, from the `Drop` implementation of its
+/// type.
+pub fn drop_nested(value: &mut dyn Detach) {
+    // Values dropped while the stack is emptied, even through types which do not detach, move
+    // their nested values to it, so nothing is left for their `Drop` to do.
+    let active = STACK.with_borrow_mut(|stack| match stack {
+        Some(stack) => {
+            value.detach(stack);
+            true
+        }
+        None => false,
+    });
+    if active {
+        return;
+    }
+    let _dropping = Dropping::start();
+    STACK.with_borrow_mut(|stack| value.detach(stack.as_mut().unwrap()));
+    while let Some(mut item) = STACK.with_borrow_mut(|stack| stack.as_mut().unwrap().pop()) {
+        STACK.with_borrow_mut(|stack| item.detach(stack.as_mut().unwrap()));
+        // Dropped outside of the borrow, as its fields may push to the stack.
+        drop(item);
+    }
+}
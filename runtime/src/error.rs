@@ -0,0 +1,69 @@
+use std::fmt;
+
+use tree_sitter::Node;
+
+use crate::Span;
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Why a syntax tree could not be converted into its typed AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A node of another kind than the one expected.
+    Kind { expected: &'static str, found: &'static str, span: Span },
+    /// A required child is missing from the node at `span`.
+    Missing { expected: &'static str, span: Span },
+    /// A child is left once its parent is parsed.
+    Unexpected { found: &'static str, span: Span },
+    /// The text of a node is not valid UTF-8.
+    Utf8 { span: Span },
+    /// Types are nested deeper than `limit` at `span`, converting them could overflow the stack.
+    TooDeep { limit: usize, span: Span },
+    /// A string which is not a keyword of the grammar.
+    UnknownKeyword(String),
+    /// The source does not match the grammar.
+    Syntax { span: Span },
+    /// The language can not be used by the linked tree-sitter library.
+    Language(String),
+    /// The parser stopped before the end of the source.
+    Cancelled,
+}
+
+impl ParseError {
+    /// `node` is not of the `expected` kind.
+    pub fn kind(expected: &'static str, node: Node<'_>) -> Self {
+        Self::Kind { expected, found: node.kind(), span: Span::of(node) }
+    }
+
+    /// `parent` has no child of the `expected` kind.
+    pub fn missing(expected: &'static str, parent: Node<'_>) -> Self {
+        Self::Missing { expected, span: Span::of(parent) }
+    }
+
+    /// `node` is left over.
+    pub fn unexpected(node: Node<'_>) -> Self {
+        Self::Unexpected { found: node.kind(), span: Span::of(node) }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Kind { expected, found, span } => {
+                write!(f, "expected `{expected}` but found `{found}` at {span}")
+            }
+            ParseError::Missing { expected, span } => write!(f, "missing `{expected}` in {span}"),
+            ParseError::Unexpected { found, span } => write!(f, "unexpected `{found}` at {span}"),
+            ParseError::Utf8 { span } => write!(f, "invalid utf-8 at {span}"),
+            ParseError::TooDeep { limit, span } => {
+                write!(f, "more than {limit} nested types at {span}")
+            }
+            ParseError::UnknownKeyword(value) => write!(f, "unknown keyword `{value}`"),
+            ParseError::Syntax { span } => write!(f, "syntax error at {span}"),
+            ParseError::Language(err) => write!(f, "bad language: {err}"),
+            ParseError::Cancelled => write!(f, "parsing was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
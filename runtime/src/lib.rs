@@ -0,0 +1,64 @@
+//! Runtime support for the ASTs generated by sitter-ast.
+//!
+//! Generated types implement [`TSParser`] and report failures as [`ParseError`], so the ASTs of
+//! several grammars can live in one crate and be handled by the same generic code.
+
+mod children;
+mod detach;
+mod error;
+mod parse;
+mod span;
+pub use children::*;
+pub use detach::*;
+pub use error::*;
+pub use parse::*;
+pub use span::*;
+
+pub use tree_sitter;
+pub use tree_sitter::Node;
+
+/// A type built from nodes of a syntax tree.
+///
+/// Types of visible rules are built from one node of their kind, the ones of hidden rules and
+/// nested types from consecutive children of their parent.
+pub trait TSParser: Sized {
+    /// Whether `Self` may start with `node`, used to choose between alternatives.
+    fn starts_with(node: Node<'_>) -> bool;
+
+    /// Builds `Self` from the next `children` of a node, consuming the ones it is made of.
+    fn parse_children(children: &mut Children<'_, '_>, source: &[u8]) -> ParseResult<Self>;
+
+    /// Builds `Self` from `root` alone.
+    fn parse(root: Node<'_>, source: &[u8]) -> ParseResult<Self> {
+        Self::parse_with_max_depth(root, source, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Builds `Self` from `root` alone, failing with [`ParseError::TooDeep`] when more than
+    /// `max_depth` types are nested.
+    fn parse_with_max_depth(root: Node<'_>, source: &[u8], max_depth: usize) -> ParseResult<Self> {
+        let mut cursor = root.walk();
+        let mut children = Children::root(&mut cursor).with_max_depth(max_depth);
+        let res = Self::parse_children(&mut children, source)?;
+        children.finish()?;
+        Ok(res)
+    }
+}
+
+impl<T: TSParser> TSParser for Box<T> {
+    fn starts_with(node: Node<'_>) -> bool {
+        T::starts_with(node)
+    }
+
+    fn parse_children(children: &mut Children<'_, '_>, source: &[u8]) -> ParseResult<Self> {
+        T::parse_children(children, source).map(Box::new)
+    }
+}
+
+/// Text of `node` in `source`.
+pub fn utf8_text<'a>(node: Node<'_>, source: &'a [u8]) -> ParseResult<&'a str> {
+    let start = node.start_byte();
+    let end = node.end_byte();
+
+    let text = if end >= start { &source[start..end] } else { &source[start..] };
+    std::str::from_utf8(text).map_err(|_| ParseError::Utf8 { span: Span::of(node) })
+}
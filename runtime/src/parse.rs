@@ -0,0 +1,57 @@
+use tree_sitter::{Language, Node, Parser, Tree};
+
+use crate::{DEFAULT_MAX_DEPTH, ParseError, ParseResult, Span, TSParser};
+
+/// Parses `source` with `language` and converts its tree into `T`.
+pub fn parse_str<T: TSParser>(language: &Language, source: &str) -> ParseResult<T> {
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|err| ParseError::Language(err.to_string()))?;
+    let tree = parser.parse(source, None).ok_or(ParseError::Cancelled)?;
+    parse_tree(&tree, source.as_bytes())
+}
+
+/// Converts the `tree` parsed from `source` into `T`, failing on its first syntax error.
+pub fn parse_tree<T: TSParser>(tree: &Tree, source: &[u8]) -> ParseResult<T> {
+    parse_tree_with_max_depth(tree, source, DEFAULT_MAX_DEPTH)
+}
+
+/// Like [`parse_tree`], failing with [`ParseError::TooDeep`] when more than `max_depth` types are
+/// nested.
+pub fn parse_tree_with_max_depth<T: TSParser>(
+    tree: &Tree,
+    source: &[u8],
+    max_depth: usize,
+) -> ParseResult<T> {
+    let root = tree.root_node();
+    if let Some(node) = first_error(root) {
+        return Err(if node.is_missing() {
+            ParseError::Missing { expected: node.kind(), span: Span::of(node) }
+        } else {
+            ParseError::Syntax { span: Span::of(node) }
+        });
+    }
+    T::parse_with_max_depth(root, source, max_depth)
+}
+
+/// The first error or missing node under `node`.
+fn first_error(node: Node<'_>) -> Option<Node<'_>> {
+    if !node.has_error() {
+        return None;
+    }
+    let mut cursor = node.walk();
+    loop {
+        let node = cursor.node();
+        if node.is_error() || node.is_missing() {
+            return Some(node);
+        }
+        // Descend into the first child containing an error, a node with an error has one.
+        if !cursor.goto_first_child() {
+            return Some(node);
+        }
+        while !cursor.node().has_error() {
+            if !cursor.goto_next_sibling() {
+                return Some(node);
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use sitter_ast_runtime::{
+    Children, Node, ParseError, ParseResult, Span, TSParser, parse_str, utf8_text,
+};
+
+#[derive(Debug, PartialEq)]
+struct Document {
+    value: String,
+}
+
+impl TSParser for Document {
+    fn starts_with(node: Node<'_>) -> bool {
+        node.kind() == "document"
+    }
+
+    fn parse_children(children: &mut Children<'_, '_>, source: &[u8]) -> ParseResult<Self> {
+        let root = children.leaf("document", Self::starts_with)?;
+        Ok(Self { value: utf8_text(root, source)?.to_string() })
+    }
+}
+
+#[test]
+fn test_parse_str() {
+    let language = tree_sitter_json::language();
+    let document: Document = parse_str(&language, "[1, 2]").unwrap();
+    assert_eq!(document, Document { value: "[1, 2]".to_string() });
+
+    let err = parse_str::<Document>(&language, "[1, 2").unwrap_err();
+    assert_eq!(err, ParseError::Missing { expected: "]", span: Span { start: 5, end: 5 } });
+
+    let err = parse_str::<Document>(&language, "[1, @, 2]").unwrap_err();
+    assert_eq!(err.to_string(), "syntax error at 4..6");
+}
+
+#[test]
+fn test_parse_tree() {
+    let source = "{\"a\": 1}";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    let document: Document = sitter_ast_runtime::parse_tree(&tree, source.as_bytes()).unwrap();
+    assert_eq!(document.value, source);
+}
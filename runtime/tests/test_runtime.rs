@@ -0,0 +1,297 @@
+use sitter_ast_runtime::{
+    Alternative, Children, Detach, Node, ParseError, ParseResult, Span, TSParser, drop_nested,
+    utf8_text,
+};
+
+#[derive(Debug, PartialEq)]
+struct Number {
+    value: String,
+    span: Span,
+}
+
+impl TSParser for Number {
+    fn starts_with(node: Node<'_>) -> bool {
+        node.kind() == "number"
+    }
+
+    fn parse_children(children: &mut Children<'_, '_>, source: &[u8]) -> ParseResult<Self> {
+        let root = children.leaf("number", Self::starts_with)?;
+        Ok(Self { value: utf8_text(root, source)?.to_string(), span: Span::of(root) })
+    }
+}
+
+#[derive(Debug)]
+struct Punctuation;
+
+impl TSParser for Punctuation {
+    fn starts_with(node: Node<'_>) -> bool {
+        !node.is_named()
+    }
+
+    fn parse_children(children: &mut Children<'_, '_>, _: &[u8]) -> ParseResult<Self> {
+        children.leaf(",", Self::starts_with)?;
+        Ok(Self)
+    }
+}
+
+#[derive(Debug)]
+struct Array {
+    numbers: Vec<Number>,
+}
+
+impl TSParser for Array {
+    fn starts_with(node: Node<'_>) -> bool {
+        node.kind() == "array"
+    }
+
+    fn parse_children(children: &mut Children<'_, '_>, source: &[u8]) -> ParseResult<Self> {
+        children.node("array", Self::starts_with, |_, children| {
+            children.parse::<Punctuation>(source)?;
+            Ok(Self { numbers: children.parse_while(source)? })
+        })
+    }
+}
+
+fn parse(source: &str) -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    parser.parse(source, None).unwrap()
+}
+
+#[test]
+fn test_children() {
+    let source = "[1, /* two */ 2, 3]";
+    let tree = parse(source);
+    let array = tree.root_node().child(0).unwrap();
+
+    let mut cursor = array.walk();
+    let mut children = Children::new(&mut cursor);
+    children.parse::<Punctuation>(source.as_bytes()).unwrap();
+    assert!(children.parse_if::<Punctuation>(source.as_bytes()).unwrap().is_none());
+    let numbers = children.parse_while::<Number>(source.as_bytes()).unwrap();
+    assert!(children.next_is(&["]"]));
+    children.finish().unwrap();
+
+    let values: Vec<_> = numbers.iter().map(|number| number.value.as_str()).collect();
+    assert_eq!(values, ["1", "2", "3"]);
+    assert_eq!(numbers[1].span, Span { start: 14, end: 15 });
+}
+
+#[test]
+fn test_node() {
+    let source = "[[1, 2], [], [3]]";
+    let tree = parse(source);
+    let array = tree.root_node().child(0).unwrap();
+
+    let mut cursor = array.walk();
+    let mut children = Children::new(&mut cursor);
+    children.parse::<Punctuation>(source.as_bytes()).unwrap();
+    let arrays = children.parse_while::<Array>(source.as_bytes()).unwrap();
+    children.finish().unwrap();
+    assert_eq!(cursor.node(), array);
+
+    let values: Vec<Vec<_>> = arrays
+        .iter()
+        .map(|array| array.numbers.iter().map(|number| number.value.as_str()).collect())
+        .collect();
+    assert_eq!(values, [vec!["1", "2"], vec![], vec!["3"]]);
+
+    let err = Array::parse(array, source.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected `array` at 1..7");
+}
+
+#[test]
+fn test_errors() {
+    let source = "[1, true]";
+    let tree = parse(source);
+    let array = tree.root_node().child(0).unwrap();
+
+    let mut cursor = array.walk();
+    let mut children = Children::new(&mut cursor);
+    children.next();
+    children.parse::<Number>(source.as_bytes()).unwrap();
+    assert_eq!(
+        children.parse::<Number>(source.as_bytes()).unwrap_err(),
+        ParseError::Kind { expected: "number", found: ",", span: Span { start: 2, end: 3 } }
+    );
+    drop(children);
+
+    let mut children = Children::new(&mut cursor);
+    children.by_ref().take(3).for_each(drop);
+    let err = children.finish().unwrap_err();
+    assert_eq!(err.to_string(), "unexpected `true` at 4..8");
+
+    let mut cursor = array.child(1).unwrap().walk();
+    let mut children = Children::new(&mut cursor);
+    let err = children.parse::<Number>(source.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "missing `number` in 1..2");
+    assert_eq!(ParseError::UnknownKeyword("x".to_string()).to_string(), "unknown keyword `x`");
+}
+
+#[derive(Debug, PartialEq)]
+enum Item {
+    Number(String),
+    True,
+}
+
+#[test]
+fn test_choose() {
+    let source = "[true, 2]";
+    let tree = parse(source);
+    let array = tree.root_node().child(0).unwrap();
+    let alternatives = [
+        Alternative {
+            starts_with: |node| node.kind() == "number",
+            nullable: false,
+            parse: |children, source| Ok(Item::Number(children.parse::<Number>(source)?.value)),
+        },
+        Alternative {
+            starts_with: |node| node.kind() == "true",
+            nullable: false,
+            parse: |children, _| {
+                children.next();
+                Ok(Item::True)
+            },
+        },
+    ];
+
+    let mut cursor = array.walk();
+    let mut children = Children::new(&mut cursor);
+    let first = children.choose(&alternatives, "item", source.as_bytes()).unwrap();
+    let second = children.choose(&alternatives, "item", source.as_bytes()).unwrap();
+    assert_eq!([first, second], [Item::True, Item::Number("2".to_string())]);
+    assert!(children.next_is(&["]"]));
+
+    let err = children.choose(&alternatives, "item", source.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "expected `item` but found `]` at 8..9");
+    children.next();
+    let err = children.choose(&alternatives, "item", source.as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "missing `item` in 0..9");
+
+    drop(children);
+    cursor.goto_first_child();
+    while cursor.node() != array.child(3).unwrap() {
+        cursor.goto_next_sibling();
+    }
+    let mut children = Children::root(&mut cursor);
+    let item = children.choose(&alternatives, "item", source.as_bytes()).unwrap();
+    assert_eq!(item, Item::Number("2".to_string()));
+    children.finish().unwrap();
+}
+
+#[test]
+fn test_too_deep() {
+    let source = "[[1], [2]]";
+    let tree = parse(source);
+    let array = tree.root_node().child(0).unwrap();
+
+    let inner = array.child(1).unwrap();
+    assert!(Array::parse_with_max_depth(inner, source.as_bytes(), 1).is_ok());
+    let mut cursor = array.walk();
+    let mut children = Children::new(&mut cursor).with_max_depth(1);
+    children.parse::<Punctuation>(source.as_bytes()).unwrap();
+    let err = children.parse::<Array>(source.as_bytes()).unwrap_err();
+    assert_eq!(err, ParseError::TooDeep { limit: 1, span: Span { start: 1, end: 2 } });
+    assert_eq!(err.to_string(), "more than 1 nested types at 1..2");
+}
+
+/// A list nesting its tail, as long as the input.
+struct Chain {
+    next: Option<Box<Chain>>,
+}
+
+impl Detach for Chain {
+    fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>) {
+        if let Some(item) = self.next.take() {
+            stack.push(item);
+        }
+    }
+
+    fn empty() -> Self {
+        Self { next: None }
+    }
+}
+
+impl Drop for Chain {
+    fn drop(&mut self) {
+        drop_nested(self);
+    }
+}
+
+#[test]
+fn test_drop_nested() {
+    let mut chain = Chain::empty();
+    for _ in 0..1_000_000 {
+        chain = Chain { next: Some(Box::new(chain)) };
+    }
+    drop(chain);
+
+    let chains = vec![Chain { next: Some(Box::new(Chain::empty())) }, Chain::empty()];
+    drop(chains);
+}
+
+/// A value holding a chain without detaching it.
+struct Statement {
+    chain: Chain,
+}
+
+/// A list nesting its tail, holding statements it does not detach.
+struct Block {
+    next: Option<Box<Block>>,
+    statements: Vec<Statement>,
+}
+
+impl Detach for Block {
+    fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>) {
+        if let Some(item) = self.next.take() {
+            stack.push(item);
+        }
+    }
+
+    fn empty() -> Self {
+        Self { next: None, statements: vec![] }
+    }
+}
+
+impl Drop for Block {
+    fn drop(&mut self) {
+        drop_nested(self);
+    }
+}
+
+#[test]
+fn test_drop_nested_wrapped() {
+    let mut chain = Chain::empty();
+    for _ in 0..1_000_000 {
+        chain = Chain { next: Some(Box::new(chain)) };
+    }
+    // The chain is dropped while the outer block empties its stack.
+    let inner = Block { next: None, statements: vec![Statement { chain }] };
+    assert!(inner.statements.iter().all(|item| item.chain.next.is_some()));
+    drop(Block { next: Some(Box::new(inner)), statements: vec![] });
+}
+
+/// A value failing to detach its nested values.
+struct Panicking;
+
+impl Detach for Panicking {
+    fn detach(&mut self, _: &mut Vec<Box<dyn Detach>>) {
+        panic!("detach");
+    }
+
+    fn empty() -> Self {
+        Self
+    }
+}
+
+#[test]
+fn test_drop_nested_after_panic() {
+    assert!(std::panic::catch_unwind(|| drop_nested(&mut Panicking)).is_err());
+
+    // Values dropped after the panic still detach their nested values instead of recursing.
+    let mut chain = Chain::empty();
+    for _ in 0..1_000_000 {
+        chain = Chain { next: Some(Box::new(chain)) };
+    }
+    drop(chain);
+}
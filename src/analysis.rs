@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use indexmap::IndexSet;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{GrammarJSON, PrecedenceValueJSON, RuleJSON};
+
+/// External token tree-sitter marks as valid while recovering from errors, which grammars declare
+/// without referencing it.
+const ERROR_RECOVERY: &str = "__error_recovery";
+
+/// Problems found in a grammar by [`GrammarJSON::analyze`], every list in grammar order.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Analysis {
+    /// Rules not reachable from the start rule, the extras or the word rule.
+    pub unreachable: Vec<String>,
+    /// Symbols referenced but neither defined as rules nor declared as externals.
+    pub undefined: Vec<String>,
+    /// Externals never referenced, literal ones quoted, but the reserved `__error_recovery`.
+    pub unused_externals: Vec<String>,
+    /// Precedence names used by rules but not declared in `precedences`.
+    pub undeclared_precedences: Vec<String>,
+    /// Names in `conflicts` which are not rules.
+    pub unknown_conflicts: Vec<String>,
+    /// Rules other than the start rule which can match the empty string.
+    pub nullable: Vec<String>,
+}
+
+impl Analysis {
+    /// Whether no problem was found.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no problems found");
+        }
+
+        let sections = [
+            ("unreachable rules", &self.unreachable),
+            ("undefined rules", &self.undefined),
+            ("unused externals", &self.unused_externals),
+            ("undeclared precedences", &self.undeclared_precedences),
+            ("unknown rules in conflicts", &self.unknown_conflicts),
+            ("rules matching the empty string", &self.nullable),
+        ];
+        for (title, items) in sections {
+            if !items.is_empty() {
+                writeln!(f, "{title}: {}", items.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GrammarJSON {
+    /// Reports the problems of the grammar tree-sitter tolerates or only finds late.
+    pub fn analyze(&self) -> Analysis {
+        let externals: Vec<_> = self.externals.iter().filter_map(symbol_name).collect();
+
+        let mut references = IndexSet::new();
+        for rule in self.rules.values().chain(&self.extras) {
+            symbols(rule, &mut references);
+        }
+        let undefined = references
+            .iter()
+            .filter(|name| !self.rules.contains_key(**name) && !externals.contains(name))
+            .map(|name| name.to_string())
+            .collect();
+
+        let mut strings = HashSet::new();
+        self.rules.values().for_each(|rule| literals(rule, &mut strings));
+        let unused_externals = self
+            .externals
+            .iter()
+            .filter_map(|item| match item {
+                RuleJSON::SYMBOL { name } if name == ERROR_RECOVERY => None,
+                RuleJSON::SYMBOL { name } => {
+                    (!references.contains(name.as_str())).then(|| name.clone())
+                }
+                RuleJSON::STRING { value } => {
+                    (!strings.contains(value.as_str())).then(|| format!("{value:?}"))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let declared: HashSet<_> = self
+            .precedences
+            .iter()
+            .flatten()
+            .filter_map(|item| match item {
+                RuleJSON::STRING { value } => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut used = IndexSet::new();
+        self.rules.values().for_each(|rule| precedence_names(rule, &mut used));
+        let undeclared_precedences =
+            used.into_iter().filter(|name| !declared.contains(name)).map(str::to_string).collect();
+
+        let unknown_conflicts = self
+            .conflicts
+            .iter()
+            .flatten()
+            .filter(|name| !self.rules.contains_key(*name))
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+
+        Analysis {
+            unreachable: self.unreachable(),
+            undefined,
+            unused_externals,
+            undeclared_precedences,
+            unknown_conflicts,
+            nullable: self.nullable(),
+        }
+    }
+
+    fn unreachable(&self) -> Vec<String> {
+        let mut reached = IndexSet::new();
+        let roots = self.rules.keys().take(1).chain(&self.word).map(String::as_str);
+        let mut stack: Vec<&str> = roots.collect();
+        let mut extras = IndexSet::new();
+        self.extras.iter().for_each(|rule| symbols(rule, &mut extras));
+        stack.extend(extras);
+
+        while let Some(name) = stack.pop() {
+            if !reached.insert(name) {
+                continue;
+            }
+            let mut next = IndexSet::new();
+            if let Some(rule) = self.rules.get(name) {
+                symbols(rule, &mut next);
+            }
+            stack.extend(next);
+        }
+
+        self.rules.keys().filter(|name| !reached.contains(name.as_str())).cloned().collect()
+    }
+
+    fn nullable(&self) -> Vec<String> {
+        let mut res = HashSet::new();
+        loop {
+            let size = res.len();
+            for (name, rule) in &self.rules {
+                if !res.contains(name.as_str()) && is_nullable(rule, &res) {
+                    res.insert(name.as_str());
+                }
+            }
+            if res.len() == size {
+                break;
+            }
+        }
+
+        // Tree-sitter lets the start rule match an empty document.
+        self.rules.keys().skip(1).filter(|name| res.contains(name.as_str())).cloned().collect()
+    }
+}
+
+fn symbol_name(rule: &RuleJSON) -> Option<&str> {
+    match rule {
+        RuleJSON::SYMBOL { name } => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether `rule` matches the empty string, given the rules known to do so.
+fn is_nullable(rule: &RuleJSON, nullable: &HashSet<&str>) -> bool {
+    match rule {
+        RuleJSON::BLANK | RuleJSON::REPEAT { .. } => true,
+        RuleJSON::STRING { value } => value.is_empty(),
+        RuleJSON::PATTERN { value, flags } => {
+            let flags = flags.as_deref().filter(|flags| !flags.is_empty());
+            let pattern = match flags {
+                Some(flags) => format!("^(?{flags}:{value})$"),
+                None => format!("^(?:{value})$"),
+            };
+            Regex::new(&pattern).is_ok_and(|pattern| pattern.is_match(""))
+        }
+        RuleJSON::SYMBOL { name } => nullable.contains(name.as_str()),
+        RuleJSON::CHOICE { members } => members.iter().any(|item| is_nullable(item, nullable)),
+        RuleJSON::SEQ { members } => members.iter().all(|item| is_nullable(item, nullable)),
+        RuleJSON::ALIAS { content, .. }
+        | RuleJSON::FIELD { content, .. }
+        | RuleJSON::REPEAT1 { content }
+        | RuleJSON::PREC_DYNAMIC { content, .. }
+        | RuleJSON::PREC_LEFT { content, .. }
+        | RuleJSON::PREC_RIGHT { content, .. }
+        | RuleJSON::PREC { content, .. }
+        | RuleJSON::TOKEN { content }
+        | RuleJSON::IMMEDIATE_TOKEN { content } => is_nullable(content, nullable),
+    }
+}
+
+fn symbols<'a>(rule: &'a RuleJSON, res: &mut IndexSet<&'a str>) {
+    rule.visit(&mut |item| {
+        if let Some(name) = symbol_name(item) {
+            res.insert(name);
+        }
+    });
+}
+
+fn literals<'a>(rule: &'a RuleJSON, res: &mut HashSet<&'a str>) {
+    rule.visit(&mut |item| {
+        if let RuleJSON::STRING { value } = item {
+            res.insert(value);
+        }
+    });
+}
+
+fn precedence_names<'a>(rule: &'a RuleJSON, res: &mut IndexSet<&'a str>) {
+    rule.visit(&mut |item| match item {
+        RuleJSON::PREC_LEFT { value: PrecedenceValueJSON::Name(name), .. }
+        | RuleJSON::PREC_RIGHT { value: PrecedenceValueJSON::Name(name), .. }
+        | RuleJSON::PREC { value: PrecedenceValueJSON::Name(name), .. } => {
+            res.insert(name);
+        }
+        _ => {}
+    });
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{GrammarJSON, naming};
+
+/// Generation settings read from a `sitter-ast.toml` (or `.json`) file.
+///
+/// ```toml
+/// language = "tree_sitter_idl::language"
+/// derives = ["Clone", "PartialEq", "Eq", "Hash"]
+///
+/// [groups]
+/// names = ["scoped_name", "identifier"]
+///
+/// [rules.scoped_name]
+/// rename = "QualifiedName"
+/// derives = ["Clone"]
+/// attributes = ["non_exhaustive"]
+/// fields = { identifier = "name" }
+/// variants = { Token1 = "Global" }
+///
+/// [rules.comment]
+/// skip = true
+///
+/// [rules.string_literal]
+/// type = "crate::literal::StringLiteral"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Path to the function returning the `tree_sitter::Language` of the grammar, needed to
+    /// generate `parse_str` on the type of the start rule.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Extra derives added to every generated type.
+    #[serde(default)]
+    pub derives: Vec<String>,
+    /// Rules generated into the same file when splitting the output, keyed by module name.
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleConfig {
+    /// Name of the generated type instead of the UpperCamel rule name.
+    pub rename: Option<String>,
+    /// Renames struct fields, keyed by their generated name.
+    pub fields: HashMap<String, String>,
+    /// Renames enum variants, keyed by their generated name.
+    pub variants: HashMap<String, String>,
+    /// Extra derives, e.g. `Clone` or `serde::Serialize`.
+    pub derives: Vec<String>,
+    /// Extra attributes without the surrounding `#[...]`.
+    pub attributes: Vec<String>,
+    /// Do not generate the rule and drop every member referencing it.
+    pub skip: bool,
+    /// Path to a user-provided type implementing `TSParser` used instead of a generated one.
+    #[serde(rename = "type")]
+    pub type_path: Option<String>,
+}
+
+impl Config {
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(toml::from_str(&content)?)
+        }
+    }
+
+    /// Checks that every configured rule exists in `grammar` and that its settings are well formed.
+    pub fn validate(&self, grammar: &GrammarJSON) -> anyhow::Result<()> {
+        if let Some(path) = &self.language {
+            syn::parse_str::<syn::Path>(path)
+                .map_err(|err| anyhow::anyhow!("bad language `{path}`: {err}"))?;
+        }
+        for item in &self.derives {
+            syn::parse_str::<syn::Path>(item)
+                .map_err(|err| anyhow::anyhow!("bad derive `{item}`: {err}"))?;
+        }
+
+        let mut grouped = HashMap::new();
+        for (group, rules) in &self.groups {
+            if syn::parse_str::<syn::Ident>(group).is_err()
+                || naming::RESERVED_MODULES.contains(&group.as_str())
+            {
+                anyhow::bail!("bad group name `{group}`");
+            }
+            for name in rules {
+                if !grammar.rules.contains_key(name) {
+                    anyhow::bail!("unknown rule `{name}` in group `{group}`");
+                }
+                if let Some(other) = grouped.insert(name, group) {
+                    anyhow::bail!("rule `{name}` is in both group `{other}` and `{group}`");
+                }
+            }
+        }
+
+        for (name, rule) in &self.rules {
+            if !grammar.rules.contains_key(name) {
+                anyhow::bail!("unknown rule `{name}` in config");
+            }
+            if rule.skip && rule.type_path.is_some() {
+                anyhow::bail!("rule `{name}` can not be both skipped and replaced by a type");
+            }
+            if let Some(rename) = &rule.rename {
+                syn::parse_str::<syn::Ident>(rename).map_err(|err| {
+                    anyhow::anyhow!("bad rename `{rename}` for rule `{name}`: {err}")
+                })?;
+            }
+            for item in rule.fields.values().chain(rule.variants.values()) {
+                syn::parse_str::<syn::Ident>(item).map_err(|err| {
+                    anyhow::anyhow!("bad rename `{item}` for rule `{name}`: {err}")
+                })?;
+            }
+            for item in &rule.derives {
+                syn::parse_str::<syn::Path>(item).map_err(|err| {
+                    anyhow::anyhow!("bad derive `{item}` for rule `{name}`: {err}")
+                })?;
+            }
+            for item in &rule.attributes {
+                syn::parse_str::<syn::Meta>(item).map_err(|err| {
+                    anyhow::anyhow!("bad attribute `{item}` for rule `{name}`: {err}")
+                })?;
+            }
+            if let Some(path) = &rule.type_path {
+                syn::parse_str::<syn::Path>(path)
+                    .map_err(|err| anyhow::anyhow!("bad type `{path}` for rule `{name}`: {err}"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use convert_case::{Case, Casing};
+use indexmap::IndexMap;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::parsers::Parsers;
+use crate::{
+    naming, recursion, Config, Grammar, Rule, RuleConfig, Shape, TokenDef, TypeDef, TypeRef,
+};
+
+/// State shared by every rule while generating a grammar.
+#[derive(Debug, Default)]
+pub struct Context {
+    config: Config,
+    tokens: BTreeMap<String, Ident>,
+    rules: IndexMap<String, Rule>,
+    boxed: HashSet<(String, String)>,
+    rule: Option<String>,
+    indirect: bool,
+    kind: Option<String>,
+    aliases: HashMap<String, Ident>,
+    idents: HashMap<String, Ident>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Finds the references between the rules of `grammar` which need a `Box`, and names their
+    /// types, numbering the hidden rules whose name clashes with another rule.
+    pub fn analyze(&mut self, grammar: &Grammar) {
+        self.rules = grammar.rules.clone();
+        self.boxed = recursion::boxed_references(grammar, &self.config);
+
+        self.idents.clear();
+        let (hidden, visible): (Vec<_>, Vec<_>) =
+            grammar.rules.keys().partition(|name| name.starts_with('_'));
+        for name in visible.into_iter().chain(hidden) {
+            let base = self.rule_ident(name);
+            let mut ident = base.clone();
+            let mut idx = 2;
+            while self.idents.values().any(|item| *item == ident) {
+                ident = ident!(&format!("{base}{idx}"));
+                idx += 1;
+            }
+            self.idents.insert(name.clone(), ident);
+        }
+    }
+
+    /// Body of the generated rule `name`.
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.get(name).filter(|_| !self.is_skipped(name))
+    }
+
+    /// Sets the node kind the next generated type is parsed from, returning the previous one.
+    pub fn set_kind(&mut self, kind: Option<&str>) -> Option<String> {
+        std::mem::replace(&mut self.kind, kind.map(str::to_string))
+    }
+
+    /// Takes the node kind of the type about to be generated, nested types have none.
+    pub(crate) fn take_kind(&mut self) -> Option<String> {
+        self.kind.take()
+    }
+
+    /// Type generated for nodes aliased as `value`.
+    pub(crate) fn alias(&self, value: &str) -> Option<Ident> {
+        self.aliases.get(value).cloned()
+    }
+
+    pub(crate) fn insert_alias(&mut self, value: &str, ident: Ident) {
+        self.aliases.insert(value.to_string(), ident);
+    }
+
+    /// Sets the rule whose types are being generated.
+    pub fn set_rule(&mut self, name: &str) {
+        self.rule = Some(name.to_string());
+    }
+
+    /// Whether the types being generated come from a hidden rule, which has no node of its own.
+    pub(crate) fn in_hidden_rule(&self) -> bool {
+        self.rule.as_ref().is_some_and(|rule| rule.starts_with('_'))
+    }
+
+    /// Marks whether the types being generated are stored behind a `Vec`, returning the previous
+    /// state.
+    pub(crate) fn set_indirect(&mut self, indirect: bool) -> bool {
+        std::mem::replace(&mut self.indirect, indirect)
+    }
+
+    /// Whether a reference to the rule `name` from the current rule needs a `Box`.
+    pub fn need_box(&self, name: &str) -> bool {
+        let Some(rule) = &self.rule else {
+            return false;
+        };
+        !self.indirect && self.boxed.contains(&(rule.clone(), name.to_string()))
+    }
+
+    /// Type of a member referencing the rule `name`, boxed when it would contain itself.
+    pub fn symbol_ref(&self, name: &str) -> TypeRef {
+        TypeRef::Type { name: self.rule_ident(name).to_string(), boxed: self.need_box(name) }
+    }
+
+    /// Type of a member referencing the nested or aliased type `ident`.
+    pub(crate) fn nested_ref(&self, ident: &Ident) -> TypeRef {
+        TypeRef::Type { name: ident.to_string(), boxed: false }
+    }
+
+    /// Name of the type generated for the rule `name`.
+    pub fn rule_ident(&self, name: &str) -> Ident {
+        if let Some(ident) = self.idents.get(name) {
+            return ident.clone();
+        }
+        match self.config.rules.get(name).and_then(|rule| rule.rename.as_ref()) {
+            Some(rename) => ident!(rename),
+            None => ident!(&name.to_case(Case::UpperCamel)),
+        }
+    }
+
+    pub fn is_skipped(&self, name: &str) -> bool {
+        self.config.rules.get(name).is_some_and(|rule| rule.skip)
+    }
+
+    /// Whether `name` is a hidden rule matching a single token, which tree-sitter keeps out of
+    /// the tree so no text of it is ever seen.
+    pub(crate) fn is_hidden_token(&self, name: &str) -> bool {
+        name.starts_with('_')
+            && matches!(self.rules.get(name), Some(Rule::Pattern { .. } | Rule::Token { .. }))
+    }
+
+    /// Whether `rule` only matches tokens tree-sitter keeps out of the tree, so a member of it
+    /// would never carry any text.
+    pub(crate) fn is_out_of_tree(&self, rule: &Rule) -> bool {
+        match rule {
+            Rule::Pattern { .. } | Rule::Token { .. } => true,
+            Rule::Symbol { name } => self.is_hidden_token(name),
+            Rule::Field { content, .. } | Rule::Repeat { content } | Rule::Repeat1 { content } => {
+                self.is_out_of_tree(content)
+            }
+            _ => false,
+        }
+    }
+
+    /// Module the rule `name` is generated in when splitting the output into files.
+    pub fn module_ident(&self, name: &str) -> Ident {
+        let group =
+            self.config.groups.iter().find(|(_, rules)| rules.iter().any(|item| item == name));
+        match group {
+            Some((group, _)) => ident!(group),
+            None => naming::module_ident(name),
+        }
+    }
+
+    /// User-provided type replacing the one generated for the rule `name`.
+    pub fn type_path(&self, name: &str) -> Option<syn::Path> {
+        let path = self.config.rules.get(name)?.type_path.as_ref()?;
+        Some(syn::parse_str(path).unwrap())
+    }
+
+    /// Configuration of the rule whose generated type is `ident`.
+    fn rule_config(&self, ident: &Ident) -> Option<&RuleConfig> {
+        self.config
+            .rules
+            .iter()
+            .find(|(name, _)| self.rule_ident(name) == *ident)
+            .map(|(_, rule)| rule)
+    }
+
+    /// Derives placed on every generated type.
+    fn derives(&self) -> Vec<&str> {
+        std::iter::once("Debug").chain(self.config.derives.iter().map(String::as_str)).collect()
+    }
+
+    /// Derives every generated type has.
+    pub(crate) fn type_derives(&self) -> Vec<syn::Path> {
+        self.derives().iter().map(|item| syn::parse_str(item).unwrap()).collect()
+    }
+
+    /// Derives of a type shared by every rule: `derives` followed by the ones configured for
+    /// every type, not those of a single rule.
+    pub(crate) fn shared_derives(&self, derives: &[&str]) -> Vec<syn::Path> {
+        let mut res = derives.to_vec();
+        for item in &self.config.derives {
+            if !res.contains(&item.as_str()) {
+                res.push(item);
+            }
+        }
+        res.iter().map(|item| syn::parse_str(item).unwrap()).collect()
+    }
+
+    /// Describes the type `ident` of the current rule, with its configured derives and
+    /// attributes.
+    pub(crate) fn type_def(&self, ident: &Ident, kind: Option<String>, shape: Shape) -> TypeDef {
+        let rule = self.rule.clone().unwrap_or_default();
+        let config = self.rule_config(ident);
+        let mut derives = vec![];
+        if !matches!(shape, Shape::Alias { .. }) {
+            let configured = config.iter().flat_map(|rule| &rule.derives);
+            for item in self.derives().into_iter().chain(configured.map(String::as_str)) {
+                if !derives.iter().any(|derive| derive == item) {
+                    derives.push(item.to_string());
+                }
+            }
+        }
+
+        TypeDef {
+            name: ident.to_string(),
+            module: self.module_ident(&rule).to_string(),
+            rule,
+            kind,
+            derives,
+            attributes: config.iter().flat_map(|rule| rule.attributes.clone()).collect(),
+            shape,
+        }
+    }
+
+    /// Name of the field `name` in the struct `ident`.
+    pub fn field_name(&self, ident: &Ident, name: Ident) -> Ident {
+        match self.rule_config(ident).and_then(|rule| rule.fields.get(&name.to_string())) {
+            Some(rename) => ident!(rename),
+            None => name,
+        }
+    }
+
+    /// Name of the variant `name` in the enum `ident`.
+    pub fn variant_name(&self, ident: &Ident, name: Ident) -> Ident {
+        match self.rule_config(ident).and_then(|rule| rule.variants.get(&name.to_string())) {
+            Some(rename) => ident!(rename),
+            None => name,
+        }
+    }
+
+    /// Interns the literal token `value` and returns the name of its type in the `tokens` module.
+    pub fn token(&mut self, value: &str) -> Ident {
+        if let Some(ident) = self.tokens.get(value) {
+            return ident.clone();
+        }
+
+        let name = naming::token_name(value);
+        let mut ident = ident!(&name);
+        let mut idx = 2;
+        while self.tokens.values().any(|item| *item == ident) {
+            ident = ident!(&format!("{name}{idx}"));
+            idx += 1;
+        }
+
+        self.tokens.insert(value.to_string(), ident.clone());
+        ident
+    }
+
+    /// Interned literal tokens, sorted by value.
+    pub fn token_defs(&self) -> Vec<TokenDef> {
+        self.tokens
+            .iter()
+            .map(|(value, ident)| TokenDef { name: ident.to_string(), value: value.clone() })
+            .collect()
+    }
+
+    /// Items of the `tokens` module, one type per interned literal token.
+    pub fn tokens(&self) -> anyhow::Result<TokenStream> {
+        let tokens = self.token_defs();
+        self.token_items(&tokens, &Parsers::new(&[], &tokens, false))
+    }
+
+    /// Items of the `tokens` module for `tokens`, parsed with `parsers`.
+    pub(crate) fn token_items(
+        &self,
+        tokens: &[TokenDef],
+        parsers: &Parsers,
+    ) -> anyhow::Result<TokenStream> {
+        let mut res = quote! {};
+
+        let derives = self.type_derives();
+        for token in tokens {
+            let ident = ident!(&token.name);
+            let parser = parsers.token_impl(token)?;
+            res.extend(quote! {
+                #[derive(#(#derives),*)]
+                pub struct #ident;
+
+                #parser
+            });
+        }
+
+        Ok(res)
+    }
+}
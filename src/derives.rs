@@ -0,0 +1,144 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Std traits a derive of the key requires on the type itself.
+const REQUIRES: &[(&str, &[&str])] = &[
+    ("Copy", &["Clone"]),
+    ("Eq", &["PartialEq"]),
+    ("PartialOrd", &["PartialEq"]),
+    ("Ord", &["Eq", "PartialOrd"]),
+];
+
+/// Std derives whose requirements on field types can be checked.
+const CHECKED: &[&str] =
+    &["Debug", "Clone", "Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash", "Default"];
+
+#[derive(Default)]
+struct Types {
+    derives: BTreeMap<String, BTreeSet<String>>,
+    fields: BTreeMap<String, Vec<syn::Type>>,
+    enums: BTreeSet<String>,
+    aliases: BTreeMap<String, syn::Type>,
+}
+
+/// Checks that every derive of the generated types can be satisfied by the types of their fields.
+pub(crate) fn check_derives(tokens: &TokenStream) -> anyhow::Result<()> {
+    let file: syn::File = syn::parse2(tokens.clone())?;
+
+    let mut types = Types::default();
+    types.collect(&file.items);
+
+    for (name, derives) in &types.derives {
+        for derive in derives {
+            if let Some((_, requires)) = REQUIRES.iter().find(|(item, _)| item == derive) {
+                if let Some(item) = requires.iter().find(|item| !derives.contains(**item)) {
+                    anyhow::bail!("type `{name}` derives `{derive}` without `{item}`");
+                }
+            }
+            if derive == "Default" && types.enums.contains(name) {
+                anyhow::bail!("type `{name}` is an enum and can not derive `Default`");
+            }
+            if !CHECKED.contains(&derive.as_str()) {
+                continue;
+            }
+
+            for field in &types.fields[name] {
+                if !types.implements(field, derive, &mut vec![]) {
+                    anyhow::bail!(
+                        "type `{name}` derives `{derive}` but its field type `{}` does not",
+                        quote!(#field)
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Types {
+    fn collect(&mut self, items: &[syn::Item]) {
+        for item in items {
+            match item {
+                syn::Item::Struct(item) => {
+                    let name = item.ident.to_string();
+                    self.derives.insert(name.clone(), derives(&item.attrs));
+                    self.fields
+                        .insert(name, item.fields.iter().map(|field| field.ty.clone()).collect());
+                }
+                syn::Item::Enum(item) => {
+                    let name = item.ident.to_string();
+                    let fields = item
+                        .variants
+                        .iter()
+                        .flat_map(|variant| variant.fields.iter().map(|field| field.ty.clone()))
+                        .collect();
+                    self.derives.insert(name.clone(), derives(&item.attrs));
+                    self.fields.insert(name.clone(), fields);
+                    self.enums.insert(name);
+                }
+                syn::Item::Type(item) => {
+                    self.aliases.insert(item.ident.to_string(), *item.ty.clone());
+                }
+                syn::Item::Mod(syn::ItemMod { content: Some((_, items)), .. }) => {
+                    self.collect(items)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether `ty` implements `derive`, types which are neither generated nor std are trusted.
+    fn implements(&self, ty: &syn::Type, derive: &str, seen: &mut Vec<String>) -> bool {
+        let syn::Type::Path(path) = ty else {
+            return true;
+        };
+        let Some(segment) = path.path.segments.last() else {
+            return true;
+        };
+        let name = segment.ident.to_string();
+
+        let args_implement = |seen: &mut Vec<String>| {
+            let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return true;
+            };
+            args.args.iter().all(|arg| match arg {
+                syn::GenericArgument::Type(ty) => self.implements(ty, derive, seen),
+                _ => true,
+            })
+        };
+
+        match name.as_str() {
+            "String" | "Vec" | "Box" => derive != "Copy" && args_implement(seen),
+            "Option" => args_implement(seen),
+            _ if seen.contains(&name) => true,
+            _ => {
+                if let Some(derives) = self.derives.get(&name) {
+                    return derives.contains(derive);
+                }
+                match self.aliases.get(&name) {
+                    Some(alias) => {
+                        seen.push(name);
+                        self.implements(alias, derive, seen)
+                    }
+                    None => true,
+                }
+            }
+        }
+    }
+}
+
+fn derives(attrs: &[syn::Attribute]) -> BTreeSet<String> {
+    let mut res = BTreeSet::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if let Some(segment) = meta.path.segments.last() {
+                res.insert(segment.ident.to_string());
+            }
+            Ok(())
+        });
+    }
+    res
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{AstModel, Cardinality, FieldDef, Shape, TypeDef, TypeRef, VariantDef};
+
+/// Changes of the generated types between two versions of a grammar, see [`AstModel::diff`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ModelDiff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// Whether code using the old types may no longer compile.
+    pub breaking: bool,
+    #[serde(flatten)]
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ChangeKind {
+    TypeAdded {
+        name: String,
+    },
+    TypeRemoved {
+        name: String,
+    },
+    /// A type generated from the same rule or node kind with the same definition.
+    TypeRenamed {
+        from: String,
+        to: String,
+    },
+    ShapeChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+    /// The element of a list or the target of an alias changed.
+    TargetChanged {
+        name: String,
+    },
+    FieldAdded {
+        name: String,
+        field: String,
+    },
+    FieldRemoved {
+        name: String,
+        field: String,
+    },
+    FieldTypeChanged {
+        name: String,
+        field: String,
+    },
+    CardinalityChanged {
+        name: String,
+        field: String,
+        from: Cardinality,
+        to: Cardinality,
+    },
+    VariantAdded {
+        name: String,
+        variant: String,
+    },
+    VariantRemoved {
+        name: String,
+        variant: String,
+    },
+    VariantTypeChanged {
+        name: String,
+        variant: String,
+    },
+    /// A literal token of the `tokens` module.
+    TokenAdded {
+        name: String,
+    },
+    TokenRemoved {
+        name: String,
+    },
+    /// A variant of the `Keyword` enum, named by its literal string.
+    KeywordAdded {
+        value: String,
+    },
+    KeywordRemoved {
+        value: String,
+    },
+}
+
+impl ModelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether any change breaks code using the old types.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.breaking)
+    }
+
+    fn push(&mut self, breaking: bool, kind: ChangeKind) {
+        self.changes.push(Change { breaking, kind });
+    }
+}
+
+impl fmt::Display for ModelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        for change in &self.changes {
+            let breaking = if change.breaking { "breaking" } else { "non-breaking" };
+            writeln!(f, "{breaking}: {}", change.kind)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::TypeAdded { name } => write!(f, "type `{name}` added"),
+            ChangeKind::TypeRemoved { name } => write!(f, "type `{name}` removed"),
+            ChangeKind::TypeRenamed { from, to } => write!(f, "type `{from}` renamed to `{to}`"),
+            ChangeKind::ShapeChanged { name, from, to } => {
+                write!(f, "type `{name}` changed from {from} to {to}")
+            }
+            ChangeKind::TargetChanged { name } => write!(f, "type `{name}` changed its target"),
+            ChangeKind::FieldAdded { name, field } => write!(f, "field `{name}::{field}` added"),
+            ChangeKind::FieldRemoved { name, field } => {
+                write!(f, "field `{name}::{field}` removed")
+            }
+            ChangeKind::FieldTypeChanged { name, field } => {
+                write!(f, "field `{name}::{field}` changed type")
+            }
+            ChangeKind::CardinalityChanged { name, field, from, to } => {
+                write!(f, "field `{name}::{field}` changed from {from} to {to}")
+            }
+            ChangeKind::VariantAdded { name, variant } => {
+                write!(f, "variant `{name}::{variant}` added")
+            }
+            ChangeKind::VariantRemoved { name, variant } => {
+                write!(f, "variant `{name}::{variant}` removed")
+            }
+            ChangeKind::VariantTypeChanged { name, variant } => {
+                write!(f, "variant `{name}::{variant}` changed type")
+            }
+            ChangeKind::TokenAdded { name } => write!(f, "token `tokens::{name}` added"),
+            ChangeKind::TokenRemoved { name } => write!(f, "token `tokens::{name}` removed"),
+            ChangeKind::KeywordAdded { value } => write!(f, "keyword `{value}` added"),
+            ChangeKind::KeywordRemoved { value } => write!(f, "keyword `{value}` removed"),
+        }
+    }
+}
+
+impl fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Cardinality::One => "one",
+            Cardinality::Optional => "optional",
+            Cardinality::Many => "many",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Shape {
+    /// Name of the kind of type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Unit => "unit",
+            Shape::Leaf => "leaf",
+            Shape::Struct { .. } => "struct",
+            Shape::Enum { .. } => "enum",
+            Shape::List { .. } => "list",
+            Shape::Alias { .. } => "alias",
+        }
+    }
+}
+
+impl AstModel {
+    /// Compares the types, literal tokens and keywords of the model with the ones of its `new`
+    /// version.
+    ///
+    /// A type missing from `new` is reported as renamed when an added one comes from the same rule
+    /// or node kind with the same definition, once the types it uses are renamed too. Added
+    /// variants are breaking unless the enum is `#[non_exhaustive]`, added keywords always are.
+    pub fn diff(&self, new: &AstModel) -> ModelDiff {
+        let mut removed: Vec<&TypeDef> =
+            self.types.iter().filter(|def| find(&new.types, &def.name).is_none()).collect();
+        let mut added: Vec<&TypeDef> =
+            new.types.iter().filter(|def| find(&self.types, &def.name).is_none()).collect();
+
+        let mut renames = HashMap::new();
+        loop {
+            let found = removed.iter().enumerate().find_map(|(idx, old)| {
+                let shape = rename(&old.shape, &renames);
+                let renamed = added.iter().position(|def| {
+                    def.shape == shape
+                        && (def.rule == old.rule || (def.kind.is_some() && def.kind == old.kind))
+                });
+                renamed.map(|item| (idx, item))
+            });
+            let Some((old, item)) = found else {
+                break;
+            };
+            renames.insert(removed.remove(old).name.clone(), added.remove(item).name.clone());
+        }
+
+        let mut res = ModelDiff::default();
+        for old in &self.types {
+            if let Some(to) = renames.get(&old.name) {
+                res.push(true, ChangeKind::TypeRenamed { from: old.name.clone(), to: to.clone() });
+            } else if let Some(new) = find(&new.types, &old.name) {
+                diff_type(&rename(&old.shape, &renames), new, &mut res);
+            } else {
+                res.push(true, ChangeKind::TypeRemoved { name: old.name.clone() });
+            }
+        }
+        for def in added {
+            res.push(false, ChangeKind::TypeAdded { name: def.name.clone() });
+        }
+
+        for token in &self.tokens {
+            if new.tokens.iter().all(|item| item.name != token.name) {
+                res.push(true, ChangeKind::TokenRemoved { name: token.name.clone() });
+            }
+        }
+        for token in &new.tokens {
+            if self.tokens.iter().all(|item| item.name != token.name) {
+                res.push(false, ChangeKind::TokenAdded { name: token.name.clone() });
+            }
+        }
+
+        for value in self.keywords.iter().filter(|value| !new.keywords.contains(value)) {
+            res.push(true, ChangeKind::KeywordRemoved { value: value.clone() });
+        }
+        for value in new.keywords.iter().filter(|value| !self.keywords.contains(value)) {
+            res.push(true, ChangeKind::KeywordAdded { value: value.clone() });
+        }
+        res
+    }
+}
+
+fn find<'a>(types: &'a [TypeDef], name: &str) -> Option<&'a TypeDef> {
+    types.iter().find(|def| def.name == name)
+}
+
+/// `shape` referencing the renamed types by their new name.
+fn rename(shape: &Shape, renames: &HashMap<String, String>) -> Shape {
+    let mut shape = shape.clone();
+    let refs: Vec<&mut TypeRef> = match &mut shape {
+        Shape::Unit | Shape::Leaf => vec![],
+        Shape::Struct { fields } => fields.iter_mut().map(|field| &mut field.ty).collect(),
+        Shape::Enum { variants } => {
+            variants.iter_mut().flat_map(|variant| &mut variant.members).collect()
+        }
+        Shape::List { element: item } | Shape::Alias { target: item } => vec![item],
+    };
+    for item in refs {
+        if let TypeRef::Type { name, .. } = item {
+            if let Some(rename) = renames.get(name) {
+                *name = rename.clone();
+            }
+        }
+    }
+    shape
+}
+
+fn diff_type(old: &Shape, new: &TypeDef, res: &mut ModelDiff) {
+    let name = &new.name;
+    match (old, &new.shape) {
+        (Shape::Struct { fields: old }, Shape::Struct { fields: new }) => {
+            diff_fields(name, old, new, res)
+        }
+        (Shape::Enum { variants: old }, Shape::Enum { variants: new_variants }) => {
+            let exhaustive = !new.attributes.iter().any(|item| item == "non_exhaustive");
+            diff_variants(name, old, new_variants, exhaustive, res)
+        }
+        (old, new) if matches!(old, Shape::Unit | Shape::Leaf) && old == new => {}
+        (Shape::List { element: old }, Shape::List { element: new })
+        | (Shape::Alias { target: old }, Shape::Alias { target: new }) => {
+            if old != new {
+                res.push(true, ChangeKind::TargetChanged { name: name.clone() });
+            }
+        }
+        (old, new) => res.push(
+            true,
+            ChangeKind::ShapeChanged {
+                name: name.clone(),
+                from: old.name().to_string(),
+                to: new.name().to_string(),
+            },
+        ),
+    }
+}
+
+fn diff_fields(name: &str, old: &[FieldDef], new: &[FieldDef], res: &mut ModelDiff) {
+    for field in old {
+        let Some(item) = new.iter().find(|item| item.name == field.name) else {
+            let field = field.name.clone();
+            res.push(true, ChangeKind::FieldRemoved { name: name.to_string(), field });
+            continue;
+        };
+        if item.cardinality != field.cardinality {
+            res.push(
+                true,
+                ChangeKind::CardinalityChanged {
+                    name: name.to_string(),
+                    field: field.name.clone(),
+                    from: field.cardinality,
+                    to: item.cardinality,
+                },
+            );
+        } else if item.ty != field.ty {
+            let field = field.name.clone();
+            res.push(true, ChangeKind::FieldTypeChanged { name: name.to_string(), field });
+        }
+    }
+
+    for item in new.iter().filter(|item| old.iter().all(|field| field.name != item.name)) {
+        let field = item.name.clone();
+        res.push(true, ChangeKind::FieldAdded { name: name.to_string(), field });
+    }
+}
+
+fn diff_variants(
+    name: &str,
+    old: &[VariantDef],
+    new: &[VariantDef],
+    exhaustive: bool,
+    res: &mut ModelDiff,
+) {
+    for variant in old {
+        let Some(item) = new.iter().find(|item| item.name == variant.name) else {
+            let variant = variant.name.clone();
+            res.push(true, ChangeKind::VariantRemoved { name: name.to_string(), variant });
+            continue;
+        };
+        if !item.fields().eq(variant.fields()) {
+            let variant = variant.name.clone();
+            res.push(true, ChangeKind::VariantTypeChanged { name: name.to_string(), variant });
+        }
+    }
+
+    for item in new.iter().filter(|item| old.iter().all(|variant| variant.name != item.name)) {
+        let variant = item.name.clone();
+        res.push(exhaustive, ChangeKind::VariantAdded { name: name.to_string(), variant });
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{Cardinality, Shape, TypeDef, TypeRef};
+
+/// Generates the `Detach` and `Drop` implementations which drop the types nesting themselves
+/// without recursing.
+///
+/// Detaching a boxed value leaves an empty one in its place, so the types needed to build the
+/// empty values implement `Detach` too.
+pub(crate) struct Drops<'a> {
+    types: HashMap<&'a str, &'a TypeDef>,
+    /// How many levels of types the empty value of each type nests, none for types without one.
+    heights: HashMap<&'a str, usize>,
+    /// Types which may nest themselves and have an empty value.
+    nested: HashSet<&'a str>,
+    /// Types implementing `Detach`: the nested ones and the ones their empty values are made of.
+    detached: HashSet<&'a str>,
+}
+
+impl<'a> Drops<'a> {
+    pub(crate) fn new(types: &'a [TypeDef]) -> Self {
+        let mut res = Self {
+            types: types.iter().map(|def| (def.name.as_str(), def)).collect(),
+            heights: HashMap::new(),
+            nested: HashSet::new(),
+            detached: HashSet::new(),
+        };
+
+        // Heights only decrease, until every type has its lowest one.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for def in types {
+                let Some(height) = res.def_height(def) else {
+                    continue;
+                };
+                if res.heights.get(def.name.as_str()).is_none_or(|old| height < *old) {
+                    res.heights.insert(&def.name, height);
+                    changed = true;
+                }
+            }
+        }
+
+        for def in types {
+            if res.heights.contains_key(def.name.as_str()) && res.reaches(def, def) {
+                res.nested.insert(&def.name);
+            }
+        }
+
+        let mut pending: Vec<_> = res.nested.iter().copied().collect();
+        while let Some(name) = pending.pop() {
+            if !res.detached.insert(name) {
+                continue;
+            }
+            let members = match &res.types[name].shape {
+                Shape::Struct { fields } => fields
+                    .iter()
+                    .filter(|field| field.cardinality == Cardinality::One)
+                    .map(|field| &field.ty)
+                    .collect(),
+                Shape::Enum { .. } => res.empty_variant(res.types[name]).fields().collect(),
+                _ => vec![],
+            };
+            for (def, _) in members.into_iter().filter_map(|item| res.resolve(item)) {
+                if !matches!(def.shape, Shape::Unit) {
+                    pending.push(&def.name);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// The type `ty` refers to through aliases, and whether it is boxed.
+    fn resolve(&self, ty: &TypeRef) -> Option<(&'a TypeDef, bool)> {
+        let TypeRef::Type { name, boxed } = ty else {
+            return None;
+        };
+        let def = *self.types.get(name.as_str())?;
+        match &def.shape {
+            Shape::Alias { target } => match self.resolve(target)? {
+                (_, true) if *boxed => None,
+                (def, inner) => Some((def, *boxed || inner)),
+            },
+            _ => Some((def, *boxed)),
+        }
+    }
+
+    /// Types of the values held by a value of `def`.
+    fn members(def: &'a TypeDef) -> Vec<&'a TypeRef> {
+        match &def.shape {
+            Shape::Struct { fields } => fields.iter().map(|field| &field.ty).collect(),
+            Shape::Enum { variants } => variants.iter().flat_map(|item| item.fields()).collect(),
+            Shape::List { element } => vec![element],
+            _ => vec![],
+        }
+    }
+
+    /// Whether a value of `from` may hold a value of `to`, at any depth.
+    fn reaches(&self, from: &'a TypeDef, to: &TypeDef) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = vec![from];
+        while let Some(def) = pending.pop() {
+            for (item, _) in Self::members(def).into_iter().filter_map(|item| self.resolve(item)) {
+                if item.name == to.name {
+                    return true;
+                }
+                if seen.insert(&item.name) {
+                    pending.push(item);
+                }
+            }
+        }
+        false
+    }
+
+    fn height(&self, ty: &TypeRef) -> Option<usize> {
+        match ty {
+            TypeRef::Token { .. } => Some(0),
+            TypeRef::Path { .. } => None,
+            TypeRef::Type { .. } => self.heights.get(self.resolve(ty)?.0.name.as_str()).copied(),
+        }
+    }
+
+    /// Height of the empty value of `def` from the heights known so far.
+    fn def_height(&self, def: &TypeDef) -> Option<usize> {
+        match &def.shape {
+            Shape::Alias { .. } => None,
+            Shape::Unit | Shape::Leaf | Shape::List { .. } => Some(0),
+            Shape::Struct { fields } => self.nested_height(
+                fields
+                    .iter()
+                    .filter(|field| field.cardinality == Cardinality::One)
+                    .map(|field| &field.ty),
+            ),
+            Shape::Enum { variants } => {
+                variants.iter().filter_map(|variant| self.nested_height(variant.fields())).min()
+            }
+        }
+    }
+
+    /// Height of a value holding `members`.
+    fn nested_height<'b>(&self, mut members: impl Iterator<Item = &'b TypeRef>) -> Option<usize> {
+        members.try_fold(0, |res, item| Some(res.max(self.height(item)? + 1)))
+    }
+
+    /// The variant of the enum `def` with the lowest empty value.
+    fn empty_variant(&self, def: &'a TypeDef) -> &'a crate::VariantDef {
+        let Shape::Enum { variants } = &def.shape else {
+            unreachable!("`{}` is not an enum", def.name);
+        };
+        variants
+            .iter()
+            .filter_map(|variant| Some((variant, self.nested_height(variant.fields())?)))
+            .min_by_key(|(_, height)| *height)
+            .unwrap()
+            .0
+    }
+
+    /// An empty value of `ty`.
+    fn empty(&self, ty: &TypeRef) -> TokenStream {
+        let Some((def, boxed)) = self.resolve(ty) else {
+            return ty.to_tokens();
+        };
+        let ident = ident!(&def.name);
+        let value = match def.shape {
+            Shape::Unit if def.has_span() => quote! { #ident { span: Span::default() } },
+            Shape::Unit => quote! { #ident },
+            _ => quote! { <#ident as Detach>::empty() },
+        };
+        match boxed {
+            true => quote! { Box::new(#value) },
+            false => value,
+        }
+    }
+
+    /// Whether values of `ty` are detached, and whether they are boxed.
+    fn detached_ref(&self, ty: &TypeRef) -> Option<bool> {
+        let (def, boxed) = self.resolve(ty)?;
+        self.nested.contains(def.name.as_str()).then_some(boxed)
+    }
+
+    /// `Detach` implementation of `def`, and `Drop` when it nests itself.
+    pub(crate) fn type_impl(&self, def: &TypeDef) -> TokenStream {
+        if !self.detached.contains(def.name.as_str()) {
+            return quote! {};
+        }
+
+        let ident = ident!(&def.name);
+        let (detach, empty) = match &def.shape {
+            Shape::Leaf => (
+                quote! {},
+                quote! { Self { value: std::string::String::new(), span: Span::default() } },
+            ),
+            Shape::List { element } => {
+                let detach = self.detached_ref(element).filter(|boxed| !boxed).map(|_| {
+                    quote! {
+                        if !self.value.is_empty() {
+                            stack.push(Box::new(std::mem::take(&mut self.value)));
+                        }
+                    }
+                });
+                let span = def.has_span().then(|| quote! { span: Span::default(), });
+                (quote! { #detach }, quote! { Self { value: Vec::new(), #span } })
+            }
+            Shape::Struct { fields } => {
+                let detach = fields.iter().filter_map(|field| {
+                    let name = ident!(&field.name);
+                    let boxed = self.detached_ref(&field.ty)?;
+                    Some(match (field.cardinality, boxed) {
+                        (Cardinality::One, true) => {
+                            let (target, _) = self.resolve(&field.ty).unwrap();
+                            let empty = self
+                                .empty(&TypeRef::Type { name: target.name.clone(), boxed: false });
+                            quote! {
+                                stack.push(Box::new(std::mem::replace(&mut *self.#name, #empty)));
+                            }
+                        }
+                        (Cardinality::One, false) => quote! { self.#name.detach(stack); },
+                        (Cardinality::Optional, true) => quote! {
+                            if let Some(item) = self.#name.take() {
+                                stack.push(item);
+                            }
+                        },
+                        (Cardinality::Optional, false) => quote! {
+                            if let Some(item) = &mut self.#name {
+                                item.detach(stack);
+                            }
+                        },
+                        (Cardinality::Many, true) => return None,
+                        (Cardinality::Many, false) => quote! {
+                            if !self.#name.is_empty() {
+                                stack.push(Box::new(std::mem::take(&mut self.#name)));
+                            }
+                        },
+                    })
+                });
+                let detach = quote! { #(#detach)* };
+
+                let empty = fields.iter().map(|field| {
+                    let name = ident!(&field.name);
+                    let value = match field.cardinality {
+                        Cardinality::One => self.empty(&field.ty),
+                        Cardinality::Optional => quote! { None },
+                        Cardinality::Many => quote! { Vec::new() },
+                    };
+                    quote! { #name: #value, }
+                });
+                let span = def.has_span().then(|| quote! { span: Span::default(), });
+                (detach, quote! { Self { #(#empty)* #span } })
+            }
+            Shape::Enum { variants } => {
+                let mut detached = false;
+                let arms: Vec<_> = variants
+                    .iter()
+                    .map(|variant| {
+                        let name = ident!(&variant.name);
+                        let mut detach = vec![];
+                        let mut items: Vec<_> = variant
+                            .fields()
+                            .enumerate()
+                            .map(|(idx, item)| match self.detached_ref(item) {
+                                Some(boxed) => {
+                                    let binding = ident!(&format!("item{idx}"));
+                                    detach.push(match boxed {
+                                        true => {
+                                            let (target, _) = self.resolve(item).unwrap();
+                                            let empty = self.empty(&TypeRef::Type {
+                                                name: target.name.clone(),
+                                                boxed: false,
+                                            });
+                                            quote! {
+                                                stack.push(Box::new(std::mem::replace(
+                                                    &mut **#binding,
+                                                    #empty,
+                                                )));
+                                            }
+                                        }
+                                        false => quote! { #binding.detach(stack); },
+                                    });
+                                    quote! { #binding }
+                                }
+                                None => quote! { _ },
+                            })
+                            .collect();
+                        items.extend(def.has_span().then(|| quote! { _ }));
+                        detached |= !detach.is_empty();
+                        match (items.is_empty(), detach.is_empty()) {
+                            (true, _) => quote! { Self::#name => {} },
+                            (false, true) => quote! { Self::#name(..) => {} },
+                            (false, false) => {
+                                quote! { Self::#name(#(#items),*) => { #(#detach)* } }
+                            }
+                        }
+                    })
+                    .collect();
+
+                let variant = self.empty_variant(def);
+                let name = ident!(&variant.name);
+                let mut items: Vec<_> = variant.fields().map(|item| self.empty(item)).collect();
+                items.extend(def.has_span().then(|| quote! { Span::default() }));
+                let empty = match items.is_empty() {
+                    true => quote! { Self::#name },
+                    false => quote! { Self::#name(#(#items),*) },
+                };
+                let detach = detached.then(|| quote! { match self { #(#arms)* } });
+                (quote! { #detach }, empty)
+            }
+            Shape::Unit | Shape::Alias { .. } => return quote! {},
+        };
+
+        let stack = match detach.is_empty() {
+            true => quote! { _ },
+            false => quote! { stack },
+        };
+        let drop = self.nested.contains(def.name.as_str()).then(|| {
+            quote! {
+                impl Drop for #ident {
+                    fn drop(&mut self) {
+                        drop_nested(self);
+                    }
+                }
+            }
+        });
+        quote! {
+            impl Detach for #ident {
+                fn detach(&mut self, #stack: &mut Vec<Box<dyn Detach>>) {
+                    #detach
+                }
+
+                fn empty() -> Self {
+                    #empty
+                }
+            }
+
+            #drop
+        }
+    }
+}
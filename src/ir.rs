@@ -0,0 +1,198 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{lists, GrammarJSON, RuleJSON};
+
+/// A grammar lowered into normalized rules, the input of code generation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Grammar {
+    pub name: String,
+    pub word: Option<String>,
+    /// Rules in grammar order, without the inlined ones.
+    pub rules: IndexMap<String, Rule>,
+    pub extras: Vec<Rule>,
+    pub externals: Vec<Rule>,
+}
+
+/// A normalized rule.
+///
+/// Precedences are dropped, nested sequences and choices are flattened and the ones with a single
+/// member collapsed, blanks only appear on their own, `optional(..)` is [`Rule::Optional`],
+/// repetitions are never nested in each other or in an optional and tokens of a literal string
+/// are that string.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Rule {
+    Blank,
+    String { value: String },
+    Pattern { value: String, flags: Option<String> },
+    Symbol { name: String },
+    Alias { content: Box<Rule>, named: bool, value: String },
+    Field { name: String, content: Box<Rule> },
+    Choice { members: Vec<Rule> },
+    Seq { members: Vec<Rule> },
+    Optional { content: Box<Rule> },
+    Repeat { content: Box<Rule> },
+    Repeat1 { content: Box<Rule> },
+    Token { content: Box<Rule>, immediate: bool },
+}
+
+impl GrammarJSON {
+    /// Lowers the grammar into normalized rules, expanding the `inline` ones where used.
+    pub fn lower(&self) -> Grammar {
+        let mut lowering = Lowering { grammar: self, inlining: vec![] };
+        let rules = self
+            .rules
+            .iter()
+            .filter(|(name, _)| !self.inline.contains(name))
+            .map(|(name, rule)| (name.clone(), lowering.lower(rule)))
+            .collect();
+
+        Grammar {
+            name: self.name.clone(),
+            word: self.word.clone(),
+            rules,
+            extras: self.extras.iter().map(|rule| lowering.lower(rule)).collect(),
+            externals: self.externals.iter().map(|rule| lowering.lower(rule)).collect(),
+        }
+    }
+}
+
+struct Lowering<'a> {
+    grammar: &'a GrammarJSON,
+    /// Inline rules being expanded, which are left as symbols when they recurse.
+    inlining: Vec<&'a str>,
+}
+
+impl<'a> Lowering<'a> {
+    fn lower(&mut self, rule: &'a RuleJSON) -> Rule {
+        match rule {
+            RuleJSON::BLANK => Rule::Blank,
+            RuleJSON::STRING { value } => Rule::String { value: value.clone() },
+            RuleJSON::PATTERN { value, flags } => {
+                Rule::Pattern { value: value.clone(), flags: flags.clone() }
+            }
+            RuleJSON::SYMBOL { name } => match self.grammar.rules.get_key_value(name) {
+                Some((name, rule))
+                    if self.grammar.inline.contains(name)
+                        && !self.inlining.contains(&name.as_str()) =>
+                {
+                    self.inlining.push(name);
+                    let res = self.lower(rule);
+                    self.inlining.pop();
+                    res
+                }
+                _ => Rule::Symbol { name: name.clone() },
+            },
+            RuleJSON::ALIAS { content, named, value } => Rule::Alias {
+                content: Box::new(self.lower(content)),
+                named: *named,
+                value: value.clone(),
+            },
+            RuleJSON::FIELD { name, content } => {
+                Rule::Field { name: name.clone(), content: Box::new(self.lower(content)) }
+            }
+            RuleJSON::CHOICE { members } => {
+                Rule::choice(members.iter().map(|item| self.lower(item)).collect())
+            }
+            RuleJSON::SEQ { members } => {
+                Rule::seq(members.iter().map(|item| self.lower(item)).collect())
+            }
+            RuleJSON::REPEAT { content } => Rule::repeat(self.lower(content)),
+            RuleJSON::REPEAT1 { content } => Rule::repeat1(self.lower(content)),
+            RuleJSON::PREC_DYNAMIC { content, .. }
+            | RuleJSON::PREC_LEFT { content, .. }
+            | RuleJSON::PREC_RIGHT { content, .. }
+            | RuleJSON::PREC { content, .. } => self.lower(content),
+            RuleJSON::TOKEN { content } => Rule::token(self.lower(content), false),
+            RuleJSON::IMMEDIATE_TOKEN { content } => Rule::token(self.lower(content), true),
+        }
+    }
+}
+
+impl Rule {
+    /// A sequence of `members`, with separated lists merged into repetitions.
+    pub(crate) fn seq(members: Vec<Rule>) -> Rule {
+        let mut res = vec![];
+        for item in members {
+            match item {
+                Rule::Seq { members } => res.extend(members),
+                Rule::Blank => {}
+                item => res.push(item),
+            }
+        }
+
+        let mut res = lists::merge_separated(res);
+        match res.len() {
+            0 => Rule::Blank,
+            1 => res.remove(0),
+            _ => Rule::Seq { members: res },
+        }
+    }
+
+    /// A choice between distinct `members`, optional if one of them matches nothing.
+    pub(crate) fn choice(members: Vec<Rule>) -> Rule {
+        let mut res: Vec<Rule> = vec![];
+        let mut optional = false;
+        let mut push = |item: Rule| {
+            if !res.contains(&item) {
+                res.push(item);
+            }
+        };
+        for item in members {
+            match item {
+                Rule::Blank => optional = true,
+                Rule::Optional { content } => {
+                    optional = true;
+                    match *content {
+                        Rule::Choice { members } => members.into_iter().for_each(&mut push),
+                        content => push(content),
+                    }
+                }
+                Rule::Choice { members } => members.into_iter().for_each(&mut push),
+                item => push(item),
+            }
+        }
+
+        let res = match res.len() {
+            0 => Rule::Blank,
+            1 => res.remove(0),
+            _ => Rule::Choice { members: res },
+        };
+        if optional { Rule::optional(res) } else { res }
+    }
+
+    pub(crate) fn optional(content: Rule) -> Rule {
+        match content {
+            Rule::Blank | Rule::Optional { .. } | Rule::Repeat { .. } => content,
+            Rule::Repeat1 { content } => Rule::Repeat { content },
+            content => Rule::Optional { content: Box::new(content) },
+        }
+    }
+
+    pub(crate) fn repeat(content: Rule) -> Rule {
+        match content {
+            Rule::Blank => Rule::Blank,
+            Rule::Optional { content } | Rule::Repeat { content } | Rule::Repeat1 { content } => {
+                Rule::repeat(*content)
+            }
+            content => Rule::Repeat { content: Box::new(content) },
+        }
+    }
+
+    /// A token of `content`, which is an anonymous node of its text for a literal string.
+    pub(crate) fn token(content: Rule, immediate: bool) -> Rule {
+        match content {
+            Rule::String { .. } => content,
+            content => Rule::Token { content: Box::new(content), immediate },
+        }
+    }
+
+    pub(crate) fn repeat1(content: Rule) -> Rule {
+        match content {
+            Rule::Blank => Rule::Blank,
+            Rule::Optional { content } | Rule::Repeat { content } => Rule::repeat(*content),
+            Rule::Repeat1 { .. } => content,
+            content => Rule::Repeat1 { content: Box::new(content) },
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+
+use log::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+use regex::Regex;
+
+use crate::{naming, Context, Grammar, Rule};
+
+/// The literal strings of `grammar` matched by its `word` rule, sorted.
+///
+/// These are the keywords tree-sitter extracts from the word token, none without a `word` rule.
+pub(crate) fn keywords(grammar: &Grammar) -> Vec<String> {
+    let Some(word) = grammar.word.as_ref() else {
+        return vec![];
+    };
+    let mut patterns = vec![];
+    if let Some(rule) = grammar.rules.get(word) {
+        word_patterns(grammar, rule, &mut patterns);
+    }
+    let pattern = format!("^(?:{})$", patterns.join("|"));
+    let pattern = match Regex::new(&pattern) {
+        Ok(pattern) if !patterns.is_empty() => pattern,
+        Ok(_) => {
+            warn!("word rule `{word}` has no pattern");
+            return vec![];
+        }
+        Err(err) => {
+            warn!("word rule `{word}` is not supported: {err}");
+            return vec![];
+        }
+    };
+
+    let mut res = BTreeSet::new();
+    for rule in grammar.rules.values() {
+        literals(rule, &mut res);
+    }
+    res.into_iter().filter(|item| pattern.is_match(item)).map(str::to_string).collect()
+}
+
+/// The `Keyword` enum listing `keywords`, empty if there are none.
+pub(crate) fn keyword_enum(keywords: &[String], ctx: &Context) -> TokenStream {
+    if keywords.is_empty() {
+        return quote! {};
+    }
+
+    let variants = naming::variant_idents(keywords);
+    let values: Vec<_> = keywords.iter().map(|value| lit_str!(value)).collect();
+    let derives = ctx.shared_derives(&[
+        "Debug",
+        "Clone",
+        "Copy",
+        "PartialEq",
+        "Eq",
+        "PartialOrd",
+        "Ord",
+        "Hash",
+    ]);
+
+    quote! {
+        /// Keywords of the grammar, the literal strings matched by its word rule.
+        #[derive(#(#derives),*)]
+        pub enum Keyword {
+            #(#variants,)*
+        }
+
+        impl Keyword {
+            pub const ALL: &'static [Keyword] = &[#(Keyword::#variants),*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(Keyword::#variants => #values,)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for Keyword {
+            type Err = ParseError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    #(#values => Ok(Keyword::#variants),)*
+                    _ => Err(ParseError::UnknownKeyword(value.to_string())),
+                }
+            }
+        }
+    }
+}
+
+/// Collects the regular expressions of the patterns `rule` matches.
+fn word_patterns(grammar: &Grammar, rule: &Rule, res: &mut Vec<String>) {
+    match rule {
+        Rule::Pattern { value, flags } => match flags.as_deref() {
+            Some(flags) if !flags.is_empty() => res.push(format!("(?{flags}:{value})")),
+            _ => res.push(format!("(?:{value})")),
+        },
+        Rule::String { value } => res.push(regex::escape(value)),
+        Rule::Symbol { name } => {
+            if let Some(rule) = grammar.rules.get(name) {
+                word_patterns(grammar, rule, res)
+            }
+        }
+        Rule::Choice { members } => {
+            members.iter().for_each(|item| word_patterns(grammar, item, res))
+        }
+        Rule::Alias { content, .. } | Rule::Token { content, .. } => {
+            word_patterns(grammar, content, res)
+        }
+        _ => warn!("unhandled case for word: {rule:?}"),
+    }
+}
+
+/// Collects the literal strings `rule` matches as tokens of their own.
+fn literals<'a>(rule: &'a Rule, res: &mut BTreeSet<&'a str>) {
+    match rule {
+        Rule::String { value } => {
+            res.insert(value);
+        }
+        Rule::Choice { members } | Rule::Seq { members } => {
+            members.iter().for_each(|item| literals(item, res))
+        }
+        Rule::Alias { content, .. }
+        | Rule::Field { content, .. }
+        | Rule::Optional { content }
+        | Rule::Repeat { content }
+        | Rule::Repeat1 { content } => literals(content, res),
+        Rule::Token { .. } | Rule::Blank | Rule::Pattern { .. } | Rule::Symbol { .. } => {}
+    }
+}
@@ -3,8 +3,6 @@
 #![allow(unused_imports)]
 #![allow(clippy::needless_borrow)]
 
-// mod f;
-mod parse_grammar;
 use std::collections::HashSet;
 
 use log::*;
@@ -26,424 +24,356 @@ macro_rules! lit_str {
         syn::LitStr::new($id, proc_macro2::Span::call_site())
     };
 }
+
+// mod f;
+mod analysis;
+mod config;
+mod context;
+mod derives;
+mod diff;
+mod drops;
+mod ir;
+mod keywords;
+mod lists;
+mod model;
+mod naming;
+mod nodes;
+mod parse_grammar;
+mod parsers;
+mod recursion;
+mod stats;
+pub use analysis::*;
+pub use config::*;
+pub use context::*;
+pub use diff::*;
+pub use ir::*;
+pub use model::*;
+pub use stats::*;
+
 impl GrammarJSON {
     pub fn to_toke_stream(&self) -> anyhow::Result<TokenStream> {
-        let mut res = quote! {
-            use tree_sitter::Node as TSNode;
+        self.to_toke_stream_with(&Config::default())
+    }
 
-            pub type ParseResult<T> = anyhow::Result<T>;
+    /// Generates the AST as a [`syn::File`].
+    pub fn to_file_with(&self, config: &Config) -> anyhow::Result<syn::File> {
+        Ok(syn::parse2(self.to_toke_stream_with(config)?)?)
+    }
 
-            trait TSParser {
-                fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self>
-                where
-                    Self: Sized;
-            }
+    /// Generates the AST as formatted source, starting with a header marking it as generated.
+    pub fn to_source_with(&self, config: &Config) -> anyhow::Result<String> {
+        let file = self.to_file_with(config)?;
+        Ok(format!("{}\n{}", self.header(), prettyplease::unparse(&file)))
+    }
+
+    /// Comment placed at the top of generated sources.
+    pub fn header(&self) -> String {
+        format!(
+            "// This file is generated by sitter-ast {} from the `{}` grammar, do not edit it by hand.\n",
+            env!("CARGO_PKG_VERSION"),
+            self.name
+        )
+    }
 
-            fn utf8_text<'a>(node: TSNode<'_>, source: &'a [u8]) -> Result<&'a str, std::str::Utf8Error> {
-                let start = node.start_byte();
-                let end = node.end_byte();
+    /// Name of the module holding the generated code, the snake case name of the grammar.
+    pub fn module_name(&self) -> String {
+        naming::field_ident(&self.name).to_string()
+    }
 
-                if end >= start {
-                    std::str::from_utf8(&source[start..end])
-                } else {
-                    std::str::from_utf8(&source[start..])
-                }
+    pub fn to_toke_stream_with(&self, config: &Config) -> anyhow::Result<TokenStream> {
+        let Generated { preamble, modules, tokens, externals } = self.generate(config)?;
+        let modules = modules.into_iter().map(|(_, items)| items);
+
+        Ok(quote! {
+            #preamble
+            #(#modules)*
+
+            pub mod tokens {
+                use super::*;
+
+                #tokens
             }
+
+            #externals
+        })
+    }
+
+    /// Generates the AST split into files, keyed by their path relative to the output directory.
+    ///
+    /// `mod.rs` holds the shared preamble, `tokens.rs` the literal tokens, `externals.rs` the
+    /// external tokens if the grammar has any, and every other file one top-level rule or one
+    /// group of rules from [`Config::groups`].
+    pub fn to_module_sources_with(&self, config: &Config) -> anyhow::Result<Vec<(String, String)>> {
+        let Generated { preamble, modules, tokens, externals } = self.generate(config)?;
+        let source = |items: TokenStream| -> anyhow::Result<String> {
+            let file = syn::parse2(items)?;
+            Ok(format!("{}\n{}", self.header(), prettyplease::unparse(&file)))
         };
-        for (name, rule) in &self.rules {
-            let ident = ident!(&name.to_case(Case::UpperCamel));
 
-            let snippet = rule.generate(&ident).unwrap();
-            res.extend(snippet);
+        let names = modules.iter().map(|(name, _)| name);
+        let externals = (!externals.is_empty()).then_some(externals);
+        let externals_mod = externals.as_ref().map(|_| {
+            quote! {
+                mod externals;
+                pub use externals::*;
+            }
+        });
+        let mut res = vec![(
+            "mod.rs".to_string(),
+            source(quote! {
+                #preamble
+
+                #(
+                    mod #names;
+                    pub use #names::*;
+                )*
+
+                pub mod tokens;
+                #externals_mod
+            })?,
+        )];
+        for (name, items) in modules {
+            res.push((format!("{name}.rs"), source(quote! { use super::*; #items })?));
+        }
+        res.push(("tokens.rs".to_string(), source(quote! { use super::*; #tokens })?));
+        if let Some(externals) = externals {
+            res.push(("externals.rs".to_string(), source(quote! { use super::*; #externals })?));
+        }
+
+        Ok(res)
+    }
+
+    fn generate(&self, config: &Config) -> anyhow::Result<Generated> {
+        let model = self.to_model_with(config)?;
+        let ctx = Context::with_config(config.clone());
+        let parsers = parsers::Parsers::new(&model.types, &model.tokens, config.language.is_some());
+        let drops = drops::Drops::new(&model.types);
+        let nodes = nodes::Nodes::new(&model);
+
+        let mut modules: Vec<(Ident, TokenStream)> = vec![];
+        let mut externals = quote! {};
+        for def in &model.types {
+            let items = [
+                def.to_tokens(),
+                parsers.type_impl(def)?,
+                drops.type_impl(def),
+                nodes.type_impl(def),
+            ]
+            .into_iter()
+            .collect();
+            if def.module == naming::EXTERNALS {
+                externals.extend(items);
+                continue;
+            }
+            let module = ident!(&def.module);
+            match modules.iter_mut().find(|(name, _)| *name == module) {
+                Some((_, module)) => module.extend(items),
+                None => modules.push((module, items)),
+            }
         }
 
-        for item in &self.externals {
-            if let RuleJSON::SYMBOL { name } = item {
-                let ident = ident!(&name.to_case(Case::UpperCamel));
-                let kind = lit_str!(name);
-                res.extend(quote! {
-                    pub struct #ident;
-
-                    impl TSParser for #ident {
-                        fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                            if root.kind() != #kind {
-                                return Err(anyhow::anyhow!("bad kind"));
-                            }
-                            Ok(Self)
-                        }
-                    }
-                })
-            } else {
-                warn!("unhanded case for externals: {item:?}");
+        if let Some(def) = model.types.iter().find(|def| model.root.as_ref() == Some(&def.name)) {
+            let items = entry_points(def, &model.name, config);
+            match modules.iter_mut().find(|(name, _)| *name == def.module) {
+                Some((_, module)) => module.extend(items),
+                None => externals.extend(items),
             }
         }
 
+        let keywords = keywords::keyword_enum(&model.keywords, &ctx);
+        let nodes = nodes.enums(&ctx);
+        let kind_ids = config.language.as_ref().map(|language| {
+            let language: syn::Path = syn::parse_str(language).unwrap();
+            parsers.kind_table(&language)
+        });
+        let preamble = quote! {
+            #[allow(unused_imports)]
+            use sitter_ast_runtime::{
+                drop_nested, utf8_text, Alternative, Children, Detach, Node as TSNode, ParseError,
+                ParseResult, Span, TSParser,
+            };
+
+            #kind_ids
+            #keywords
+            #nodes
+        };
+
+        let tokens = ctx.token_items(&model.tokens, &parsers)?;
+        let res = Generated { preamble, modules, tokens, externals };
+        derives::check_derives(
+            &res.modules
+                .iter()
+                .map(|(_, items)| items)
+                .chain([&res.preamble, &res.tokens, &res.externals])
+                .cloned()
+                .collect(),
+        )?;
         Ok(res)
     }
 }
 
-impl RuleJSON {
-    pub fn generate(&self, ident: &Ident) -> anyhow::Result<TokenStream> {
-        trace!("generate: {ident} - {self:?}");
-
-        let mut res = quote! {};
+/// `parse_tree` and, when the language is configured, `parse_str` on the root type `def`, none
+/// when it is another name for a type not parsed from the root node.
+fn entry_points(def: &TypeDef, grammar: &str, config: &Config) -> TokenStream {
+    if def.kind.is_none() || matches!(def.shape, Shape::Alias { .. }) {
+        warn!("the root type `{}` has no node of its own, no entry point is generated", def.name);
+        return quote! {};
+    }
 
-        match self {
-            RuleJSON::ALIAS { content, named, value: _ } => {
-                if *named {
-                    res.extend(content.generate(ident)?);
-                }
+    let ident = ident!(&def.name);
+    let parse_str = config.language.as_ref().map(|language| {
+        let language: syn::Path = syn::parse_str(language).unwrap();
+        let doc = format!(" Parses `source` as a whole `{grammar}` document.");
+        quote! {
+            #[doc = #doc]
+            pub fn parse_str(source: &str) -> ParseResult<Self> {
+                sitter_ast_runtime::parse_str(&#language(), source)
             }
-            RuleJSON::BLANK => {}
-            RuleJSON::STRING { value } => {
-                let kind = lit_str!(value);
-
-                res.extend(quote! {
-                    #[derive(Debug)]
-                    pub struct #ident;
-
-                    impl TSParser for #ident {
-                        fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                            if root.kind() != #kind {
-                                return Err(anyhow::anyhow!("bad kind"));
-                            }
-                            Ok(Self)
-                        }
-                    }
-                })
+        }
+    });
+    quote! {
+        impl #ident {
+            #parse_str
+
+            /// Converts the `tree` parsed from `source`, failing on its first syntax error.
+            pub fn parse_tree(
+                tree: &sitter_ast_runtime::tree_sitter::Tree,
+                source: &[u8],
+            ) -> ParseResult<Self> {
+                sitter_ast_runtime::parse_tree(tree, source)
             }
-            RuleJSON::PATTERN { value: _, flags: _ } => res.extend(quote! {
-                #[derive(Debug)]
-                pub struct #ident{
-                    value: String
-                }
+        }
+    }
+}
 
-                impl TSParser for #ident {
-                    fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                        Ok(Self {
-                            value: utf8_text(root, source)?.to_string()
-                        })
-                    }
+/// Generated items, grouped by the module they belong to.
+struct Generated {
+    preamble: TokenStream,
+    modules: Vec<(Ident, TokenStream)>,
+    tokens: TokenStream,
+    externals: TokenStream,
+}
+
+impl Rule {
+    /// Generates the items of the rule, named `ident`.
+    pub fn generate(&self, ident: &Ident, ctx: &mut Context) -> anyhow::Result<TokenStream> {
+        let types = self.build(ident, ctx)?;
+        let parsers = parsers::Parsers::new(&types, &[], false);
+        let drops = drops::Drops::new(&types);
+        let mut res = quote! {};
+        for def in &types {
+            res.extend([def.to_tokens(), parsers.type_impl(def)?, drops.type_impl(def)]);
+        }
+        Ok(res)
+    }
+}
+
+impl TypeDef {
+    /// Rust definition of the type, without its `TSParser` implementation.
+    pub fn to_tokens(&self) -> TokenStream {
+        let ident = ident!(&self.name);
+        let derives = self.derives.iter().map(|item| syn::parse_str::<syn::Path>(item).unwrap());
+        let attributes =
+            self.attributes.iter().map(|item| syn::parse_str::<syn::Meta>(item).unwrap());
+        let attributes = quote! {
+            #[derive(#(#derives),*)]
+            #(#[#attributes])*
+        };
+        let span = self.has_span().then(|| quote! { pub span: Span, });
+
+        match &self.shape {
+            Shape::Alias { target } => {
+                let target = target.to_tokens();
+                quote! {
+                    pub type #ident = #target;
                 }
-            }),
-            RuleJSON::SYMBOL { name } => {
-                let target_ident = ident!(&name.to_case(Case::UpperCamel));
-                res.extend(quote! {
-                    pub type #ident = #target_ident;
-                })
             }
-            RuleJSON::CHOICE { members } => {
-                let mut mem = quote! {};
-                for (idx, item) in members.iter().enumerate() {
-                    match item {
-                        RuleJSON::STRING { value: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::UpperCamel));
-                            mem.extend(quote! {
-                                #field_name,
-                            });
-                        }
-                        RuleJSON::SYMBOL { name } => {
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            let need_box = is_recursive_type(&ident.to_string(), item);
-                            if need_box {
-                                mem.extend(quote! {
-                                    #field_type(Box<#field_type>),
-                                });
-                            } else {
-                                mem.extend(quote! {
-                                    #field_type(#field_type),
-                                });
-                            }
-                        }
-                        RuleJSON::SEQ { members } => {
-                            let is_consis_by_symbol = members.iter().all(|item| {
-                                matches!(
-                                    item,
-                                    RuleJSON::STRING { value: _ } | RuleJSON::SYMBOL { name: _ }
-                                )
-                            });
-
-                            if is_consis_by_symbol {
-                                let mut mid = quote! {};
-                                let mut childs = vec![];
-
-                                for item in members {
-                                    match item {
-                                        RuleJSON::SYMBOL { name } => {
-                                            childs.push(name.clone());
-                                            let need_box =
-                                                is_recursive_type(&ident.to_string(), item);
-                                            let ident = ident!(&name.to_case(Case::UpperCamel));
-                                            if need_box {
-                                                mid.extend(quote! {
-                                                    Box<#ident>,
-                                                });
-                                            } else {
-                                                mid.extend(quote! {
-                                                    #ident,
-                                                });
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                                let sub = ident!(
-                                    &format!("{ident}_TOKEN_{idx}").to_case(Case::UpperCamel)
-                                );
-                                mem.extend(quote! {
-                                    #sub(#mid),
-                                });
-                            } else {
-                                let field_type = ident!(
-                                    &format!("{ident}_TOKEN_{idx}").to_case(Case::UpperCamel)
-                                );
-                                res.extend(item.generate(&field_type));
-                                mem.extend(quote! {
-                                    #field_type(#field_type),
-                                });
-                            }
-                        }
-                        RuleJSON::ALIAS { content: _, named, value: _ } => {
-                            if !named {
-                                continue;
-                            }
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(item.generate(&field_type)?);
-
-                            mem.extend(quote! {
-                                #field_type(#field_type),
-                            });
-                        }
-                        RuleJSON::BLANK => mem.extend(quote! {
-                            Blank,
-                        }),
-                        RuleJSON::CHOICE { members: _ }
-                        | RuleJSON::PATTERN { value: _, flags: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(item.generate(&field_type)?);
-
-                            mem.extend(quote! {
-                                #field_type(#field_type),
-                            });
-                        }
-                        _ => {
-                            warn!("unhandled case for CHOICE: {item:?}");
-                        }
-                    }
+            Shape::Unit if self.has_span() => quote! {
+                #attributes
+                pub struct #ident {
+                    #span
                 }
-
-                res.extend(quote! {
-                    #[derive(Debug)]
-                    pub enum #ident {
-                        #mem
-                    }
-
-                    impl TSParser for #ident {
-                        fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                            todo!()
-                        }
+            },
+            Shape::Unit => quote! {
+                #attributes
+                pub struct #ident;
+            },
+            Shape::Leaf => quote! {
+                #attributes
+                pub struct #ident {
+                    pub value: std::string::String,
+                    pub span: Span,
+                }
+            },
+            Shape::Enum { variants } => {
+                let variants = variants.iter().map(|variant| {
+                    let name = ident!(&variant.name);
+                    let mut fields: Vec<_> = variant.fields().map(TypeRef::to_tokens).collect();
+                    fields.extend(self.has_span().then(|| quote! { Span }));
+                    if fields.is_empty() {
+                        quote! { #name, }
+                    } else {
+                        quote! { #name(#(#fields),*), }
                     }
-
                 });
-            }
-            RuleJSON::FIELD { name, content } => {
-                let ident = ident!(&format!("{ident}_{name}").to_case(Case::UpperCamel));
-                res.extend(content.generate(&ident)?);
-                let field_name = lit_str!(name);
-
-                res.extend(quote! {
-                    impl #ident {
-                        pub fn field_name() -> &str {
-                            #field_name
-                        }
+                quote! {
+                    #attributes
+                    pub enum #ident {
+                        #(#variants)*
                     }
-                })
+                }
             }
-            RuleJSON::SEQ { members } => {
-                let mut mem = quote! {};
-
-                let mut alls = HashSet::new();
-
-                for (idx, item) in members.iter().enumerate() {
-                    match item {
-                        RuleJSON::SYMBOL { name } => {
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            let field_name = {
-                                let mut name = name.clone();
-                                if alls.contains(&name) {
-                                    name += &format!("_{idx}");
-                                } else {
-                                    alls.insert(name.clone());
-                                }
-
-                                ident!(&name.to_case(Case::Snake))
-                            };
-
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            });
-                        }
-                        RuleJSON::FIELD { name: _, content } => match content.as_ref() {
-                            RuleJSON::SYMBOL { name } => {
-                                let field_name = ident!(&name.to_case(Case::Snake));
-                                let field_type = ident!(&name.to_case(Case::UpperCamel));
-                                mem.extend(quote! {
-                                    pub #field_name: #field_type,
-                                })
-                            }
-                            RuleJSON::CHOICE { members: _ } => {
-                                let name = format!("{ident}_TOKEN_{idx}");
-
-                                let field_name = ident!(&name.to_case(Case::Snake));
-                                let field_type = ident!(&name.to_case(Case::UpperCamel));
-                                res.extend(content.generate(&field_type));
-                                mem.extend(quote! {
-                                    pub #field_name: #field_type,
-                                });
-                            }
-                            _ => {}
-                        },
-                        RuleJSON::CHOICE { members: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            res.extend(item.generate(&field_type));
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            })
-                        }
-                        RuleJSON::STRING { value: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(item.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            });
-                        }
-                        RuleJSON::PATTERN { value: _, flags: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(item.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            });
-                        }
-                        RuleJSON::IMMEDIATE_TOKEN { content } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(content.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            });
-                        }
-                        RuleJSON::REPEAT1 { content } | RuleJSON::REPEAT { content } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-                            res.extend(content.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: Vec<#field_type>,
-                            });
-                        }
-                        RuleJSON::SEQ { members: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-
-                            res.extend(item.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            })
-                        }
-                        RuleJSON::TOKEN { content: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-
-                            res.extend(item.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            })
-                        }
-                        RuleJSON::ALIAS { content: _, named: _, value: _ } => {
-                            let name = format!("{ident}_TOKEN_{idx}");
-                            let field_name = ident!(&name.to_case(Case::Snake));
-                            let field_type = ident!(&name.to_case(Case::UpperCamel));
-
-                            res.extend(item.generate(&field_type)?);
-                            mem.extend(quote! {
-                                pub #field_name: #field_type,
-                            })
-                        }
-                        _ => {
-                            warn!("unhandled case for SEQ: {item:?}");
-                        }
+            Shape::Struct { fields } => {
+                let fields = fields.iter().map(|FieldDef { name, ty, cardinality }| {
+                    let name = ident!(name);
+                    let ty = ty.to_tokens();
+                    match cardinality {
+                        Cardinality::One => quote! { pub #name: #ty, },
+                        Cardinality::Optional => quote! { pub #name: Option<#ty>, },
+                        Cardinality::Many => quote! { pub #name: Vec<#ty>, },
                     }
-                }
-                res.extend(quote! {
-                    #[derive(Debug)]
+                });
+                quote! {
+                    #attributes
                     pub struct #ident {
-                        #mem
+                        #(#fields)*
+                        #span
                     }
-
-                    impl TSParser for #ident {
-                        fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                            todo!()
-                        }
-                    }
-                });
+                }
             }
-            RuleJSON::REPEAT1 { content } | RuleJSON::REPEAT { content } => {
-                let field_type = ident!(&format!("{}_TOKEN", ident).to_case(Case::UpperCamel));
-
-                res.extend(content.generate(&field_type));
-
-                res.extend(quote! {
-                    #[derive(Debug)]
+            Shape::List { element } => {
+                let element = element.to_tokens();
+                quote! {
+                    #attributes
                     pub struct #ident {
-                        value: Vec<#field_type>
+                        pub value: Vec<#element>,
+                        #span
                     }
-
-                    impl TSParser for #ident {
-                        fn parse(root: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
-                            let mut value = vec![];
-
-                            for node in root.child(&mut root.walk()){
-                                value.push(TSParser::parser(node, source)?);
-                            }
-
-                            Ok(Self {
-                                value
-                            })
-                        }
-                    }
-                });
-            }
-            RuleJSON::PREC_DYNAMIC { value: _, content }
-            | RuleJSON::PREC_LEFT { value: _, content }
-            | RuleJSON::PREC_RIGHT { value: _, content }
-            | RuleJSON::PREC { value: _, content }
-            | RuleJSON::TOKEN { content }
-            | RuleJSON::IMMEDIATE_TOKEN { content } => {
-                res.extend(content.generate(ident)?);
+                }
             }
         }
-
-        Ok(res)
     }
 }
 
-fn is_recursive_type(ident: &str, value: &RuleJSON) -> bool {
-    match value {
-        RuleJSON::SYMBOL { name } => ident == name.to_case(Case::UpperCamel),
-        RuleJSON::SEQ { members } => members.iter().all(|item| !is_recursive_type(ident, item)),
-        RuleJSON::ALIAS { content, named: _, value: _ } => is_recursive_type(ident, content),
-        _ => false,
+impl TypeRef {
+    pub fn to_tokens(&self) -> TokenStream {
+        match self {
+            TypeRef::Type { name, boxed: false } => {
+                let ident = ident!(name);
+                quote! { #ident }
+            }
+            TypeRef::Type { name, boxed: true } => {
+                let ident = ident!(name);
+                quote! { Box<#ident> }
+            }
+            TypeRef::Token { name } => {
+                let ident = ident!(name);
+                quote! { tokens::#ident }
+            }
+            TypeRef::Path { path } => {
+                let path: syn::Path = syn::parse_str(path).unwrap();
+                quote! { #path }
+            }
+        }
     }
 }
@@ -0,0 +1,43 @@
+use crate::Rule;
+
+/// Replaces every separated list such as `x, repeat(seq(",", x))` among the `members` of a
+/// sequence, optionally followed by a trailing separator, by the repetition of its element, so
+/// that separators are left out of the generated fields.
+pub(crate) fn merge_separated(members: Vec<Rule>) -> Vec<Rule> {
+    let mut res = vec![];
+    let mut members = members.into_iter().peekable();
+    while let Some(item) = members.next() {
+        let Some(separator) = members.peek().and_then(|next| separated_by(next, &item)) else {
+            res.push(item);
+            continue;
+        };
+        members.next();
+        members.next_if(|next| is_optional(next, &separator));
+        res.push(Rule::Repeat1 { content: Box::new(item) });
+    }
+    res
+}
+
+/// The separator of `rule` if it is `repeat(seq(separator, element))`.
+fn separated_by(rule: &Rule, element: &Rule) -> Option<String> {
+    let Rule::Repeat { content } = rule else {
+        return None;
+    };
+    match content.as_ref() {
+        Rule::Seq { members } => match members.as_slice() {
+            [Rule::String { value }, item] if item == element => Some(value.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `rule` is `optional(separator)`.
+fn is_optional(rule: &Rule, separator: &str) -> bool {
+    match rule {
+        Rule::Optional { content } => {
+            matches!(content.as_ref(), Rule::String { value } if value == separator)
+        }
+        _ => false,
+    }
+}
@@ -4,40 +4,204 @@
 #![allow(unused_imports)]
 #![allow(clippy::needless_borrow)]
 
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::Context;
 use clap::Parser;
 use proc_macro2::Span;
 use quote::quote;
+use sitter_ast::Config;
 use sitter_ast::GrammarJSON;
 use sitter_ast::RuleJSON;
 
 #[derive(clap::Parser)]
 struct Args {
-    file: String,
-    #[clap(short)]
-    output: Option<String>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// generate the ast types of a grammar.json
+    Generate {
+        file: String,
+        #[clap(short)]
+        output: Option<String>,
+        #[clap(flatten)]
+        options: Options,
+        /// write one file per rule into the output directory instead of a single file
+        #[clap(long, requires = "output")]
+        split: bool,
+    },
+    /// check that the ast types of a grammar.json can be generated, without writing them
+    Check {
+        file: String,
+        #[clap(flatten)]
+        options: Options,
+    },
+    /// print the model of the generated types as json
+    Model {
+        file: String,
+        #[clap(flatten)]
+        options: Options,
+    },
+    /// report the changes of the generated types between two versions of a grammar.json
+    Diff {
+        old: String,
+        new: String,
+        #[clap(flatten)]
+        options: Options,
+        /// print the changes as json
+        #[clap(long)]
+        json: bool,
+    },
+    /// pretty-print the parsed tree of a rule
+    Dump { file: String, rule: String },
+    /// count the rules and generated types of a grammar.json
+    Stats {
+        file: String,
+        #[clap(flatten)]
+        options: Options,
+        /// print the counts as json
+        #[clap(long)]
+        json: bool,
+    },
+    /// report unreachable, undefined and empty rules and other grammar problems
+    Analyze {
+        file: String,
+        /// print the report as json
+        #[clap(long)]
+        json: bool,
+    },
 }
 
-fn main() {
+#[derive(clap::Args)]
+struct Options {
+    /// sitter-ast.toml (or .json) with per-rule settings
+    #[clap(short, long)]
+    config: Option<String>,
+    /// extra derives for every generated type, e.g. `Clone,PartialEq`
+    #[clap(long, value_delimiter = ',')]
+    derive: Vec<String>,
+}
+
+impl Options {
+    fn config(self) -> anyhow::Result<Config> {
+        let mut config = match self.config {
+            Some(path) => Config::from_file(path)?,
+            None => Config::default(),
+        };
+        config.derives.extend(self.derive);
+        Ok(config)
+    }
+}
+
+fn main() -> ExitCode {
     setup_log();
-    let args = Args::parse();
-    let contnet = std::fs::read_to_string(args.file).unwrap();
+    match run(Args::parse().command) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let ast: GrammarJSON = serde_json::from_str(&contnet).unwrap();
-    let mut res = quote! {};
+fn run(command: Command) -> anyhow::Result<ExitCode> {
+    match command {
+        Command::Generate { file, output, options, split } => {
+            let ast = read_grammar(&file)?;
+            let config = options.config()?;
 
-    let snippet = ast.to_toke_stream().unwrap();
-    res.extend(snippet);
+            // A single file written into a directory is named after the grammar, e.g. `src/json.rs`.
+            let output = output.map(|output| {
+                let path = Path::new(&output);
+                if !split && path.is_dir() {
+                    path.join(format!("{}.rs", ast.module_name())).display().to_string()
+                } else {
+                    output
+                }
+            });
 
-    // println!("{:#?}", ast);
-    if let Some(output) = args.output {
-        std::fs::write(output, res.to_string()).unwrap();
-    } else {
-        println!("{:}", res);
+            if split {
+                let output = Path::new(output.as_deref().unwrap_or_default());
+                std::fs::create_dir_all(output)
+                    .with_context(|| format!("can not create `{}`", output.display()))?;
+                for (name, source) in ast.to_module_sources_with(&config)? {
+                    let path = output.join(name);
+                    std::fs::write(&path, source)
+                        .with_context(|| format!("can not write `{}`", path.display()))?;
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let res = ast.to_source_with(&config)?;
+            match output {
+                Some(output) => std::fs::write(&output, res)
+                    .with_context(|| format!("can not write `{output}`"))?,
+                None => print!("{res}"),
+            }
+        }
+        Command::Check { file, options } => {
+            read_grammar(&file)?.to_file_with(&options.config()?)?;
+        }
+        Command::Model { file, options } => {
+            let model = read_grammar(&file)?.to_model_with(&options.config()?)?;
+            println!("{}", serde_json::to_string_pretty(&model)?);
+        }
+        Command::Diff { old, new, options, json } => {
+            let config = options.config()?;
+            let old = read_grammar(&old)?.to_model_with(&config)?;
+            let new = read_grammar(&new)?.to_model_with(&config)?;
+            let diff = old.diff(&new);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print!("{diff}");
+            }
+            if diff.is_breaking() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Dump { file, rule } => {
+            let ast = read_grammar(&file)?;
+            let Some(rule) = ast.rules.get(&rule) else {
+                anyhow::bail!("unknown rule `{rule}`");
+            };
+            println!("{rule:#?}");
+        }
+        Command::Stats { file, options, json } => {
+            let stats = read_grammar(&file)?.stats(&options.config()?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print!("{stats}");
+            }
+        }
+        Command::Analyze { file, json } => {
+            let analysis = read_grammar(&file)?.analyze();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&analysis)?);
+            } else {
+                print!("{analysis}");
+            }
+            if !analysis.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
     }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn read_grammar(file: &str) -> anyhow::Result<GrammarJSON> {
+    let contnet =
+        std::fs::read_to_string(file).with_context(|| format!("can not read `{file}`"))?;
+    serde_json::from_str(&contnet).with_context(|| format!("bad grammar `{file}`"))
 }
 
 fn setup_log() {
-    use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+    use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
     tracing_subscriber::registry()
         .with(EnvFilter::from_default_env())
@@ -0,0 +1,501 @@
+use std::collections::HashSet;
+
+use convert_case::{Case, Casing};
+use log::*;
+use serde::Serialize;
+use syn::Ident;
+
+use crate::{keywords, naming, nodes, Config, Context, GrammarJSON, Rule};
+
+/// What is generated for a grammar: every type with its fields or variants, the literal tokens,
+/// the keywords and the node kinds, independently of how it is written out as Rust.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AstModel {
+    /// Name of the grammar.
+    pub name: String,
+    /// Type of the start rule, the root of parsed trees.
+    pub root: Option<String>,
+    /// Types in the order they are generated, nested types before the ones using them.
+    pub types: Vec<TypeDef>,
+    /// Literal tokens of the `tokens` module, sorted by value.
+    pub tokens: Vec<TokenDef>,
+    /// Literal strings matched by the word rule, sorted.
+    pub keywords: Vec<String>,
+    /// Named node kinds, the visible rules and external tokens then the named aliases.
+    pub kinds: Vec<String>,
+}
+
+/// A generated type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeDef {
+    pub name: String,
+    /// Rule the type is generated from.
+    pub rule: String,
+    /// Module the type is written in when splitting the output into files.
+    pub module: String,
+    /// Node kind checked when parsing, none for hidden rules and nested types.
+    pub kind: Option<String>,
+    pub derives: Vec<String>,
+    /// Attributes other than derives, without `#[..]`.
+    pub attributes: Vec<String>,
+    #[serde(flatten)]
+    pub shape: Shape,
+}
+
+impl TypeDef {
+    /// Whether values keep the span of the node they are parsed from, as leaves and the types of
+    /// node kinds do: in a `span` field, or as the last member of every variant of an enum.
+    pub fn has_span(&self) -> bool {
+        match self.shape {
+            Shape::Leaf => true,
+            Shape::Alias { .. } => false,
+            _ => self.kind.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Shape {
+    /// A literal node without data.
+    Unit,
+    /// A leaf node of a kind, keeping its text and span.
+    Leaf,
+    Struct {
+        fields: Vec<FieldDef>,
+    },
+    Enum {
+        variants: Vec<VariantDef>,
+    },
+    /// The repeated children of a node.
+    List {
+        element: TypeRef,
+    },
+    /// Another name for an existing type.
+    Alias {
+        target: TypeRef,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: TypeRef,
+    pub cardinality: Cardinality,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cardinality {
+    One,
+    Optional,
+    Many,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VariantDef {
+    pub name: String,
+    /// What the variant matches in order: its literal tokens are not kept, the other members are
+    /// the types of the tuple variant.
+    pub members: Vec<TypeRef>,
+}
+
+impl VariantDef {
+    /// Types of the tuple variant, none for a unit variant.
+    pub fn fields(&self) -> impl Iterator<Item = &TypeRef> {
+        self.members.iter().filter(|item| !matches!(item, TypeRef::Token { .. }))
+    }
+}
+
+/// A reference to a type from a field, variant or alias.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeRef {
+    /// A generated type, boxed when it would contain itself.
+    Type { name: String, boxed: bool },
+    /// A literal token of the `tokens` module.
+    Token { name: String },
+    /// A user-provided type.
+    Path { path: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenDef {
+    pub name: String,
+    pub value: String,
+}
+
+impl GrammarJSON {
+    pub fn to_model(&self) -> anyhow::Result<AstModel> {
+        self.to_model_with(&Config::default())
+    }
+
+    /// Builds the model of the types generated for the grammar with `config`.
+    pub fn to_model_with(&self, config: &Config) -> anyhow::Result<AstModel> {
+        config.validate(self)?;
+
+        let grammar = self.lower();
+        let mut ctx = Context::with_config(config.clone());
+        ctx.analyze(&grammar);
+
+        let mut types = vec![];
+        let mut rules: Vec<_> = grammar.rules.iter().collect();
+        rules.sort_by_key(|(name, _)| *name);
+        for (name, rule) in rules {
+            if ctx.is_skipped(name) || ctx.is_hidden_token(name) {
+                continue;
+            }
+            let ident = ctx.rule_ident(name);
+            ctx.set_rule(name);
+            ctx.set_kind((!name.starts_with('_')).then_some(name.as_str()));
+
+            match config.rules.get(name).and_then(|rule| rule.type_path.clone()) {
+                Some(path) => {
+                    let target = TypeRef::Path { path };
+                    types.push(ctx.type_def(&ident, None, Shape::Alias { target }));
+                }
+                None => types.extend(rule.build(&ident, &mut ctx)?),
+            }
+        }
+
+        let keywords = if grammar.rules.keys().any(|name| ctx.rule_ident(name) == "Keyword") {
+            warn!("a rule is named `Keyword`, no keyword enum is generated");
+            vec![]
+        } else {
+            keywords::keywords(&grammar)
+        };
+
+        for item in &grammar.externals {
+            match item {
+                Rule::String { value } => {
+                    ctx.token(value);
+                }
+                Rule::Symbol { name } if self.rules.contains_key(name) => {}
+                Rule::Symbol { name } => {
+                    let ident = ctx.rule_ident(name);
+                    let mut def = ctx.type_def(&ident, Some(name.clone()), Shape::Leaf);
+                    def.rule = name.clone();
+                    def.module = naming::EXTERNALS.to_string();
+                    types.push(def);
+                }
+                _ => warn!("unhandled case for externals: {item:?}"),
+            }
+        }
+
+        let root = grammar.rules.keys().next().filter(|name| !ctx.is_skipped(name));
+        Ok(AstModel {
+            name: self.name.clone(),
+            root: root.map(|name| ctx.rule_ident(name).to_string()),
+            types,
+            tokens: ctx.token_defs(),
+            keywords,
+            kinds: nodes::node_kinds(&grammar),
+        })
+    }
+}
+
+impl Rule {
+    /// Builds the types of the rule, named `ident`, after the nested types they use.
+    pub fn build(&self, ident: &Ident, ctx: &mut Context) -> anyhow::Result<Vec<TypeDef>> {
+        trace!("build: {ident} - {self:?}");
+
+        let mut res = vec![];
+        let mut kind = ctx.take_kind();
+
+        let shape = match self {
+            Rule::Alias { content, named, value } => {
+                let target = if *named {
+                    alias_type(content, value, ctx, &mut res)?
+                } else {
+                    TypeRef::Token { name: ctx.token(value).to_string() }
+                };
+                Shape::Alias { target }
+            }
+            Rule::Blank => return Ok(res),
+            Rule::String { value } if kind.is_none() && ctx.in_hidden_rule() => {
+                Shape::Alias { target: TypeRef::Token { name: ctx.token(value).to_string() } }
+            }
+            Rule::String { value } => {
+                kind = kind.or_else(|| Some(value.clone()));
+                Shape::Unit
+            }
+            // Only the tokens of a rule have a node, the ones nested in a rule are out of the tree
+            // and no text of them is ever seen.
+            Rule::Pattern { value: _, flags: _ } | Rule::Token { .. } if kind.is_none() => {
+                Shape::Unit
+            }
+            Rule::Pattern { value: _, flags: _ } | Rule::Token { .. } => Shape::Leaf,
+            // A visible rule of a single symbol has a node of its own wrapping the other one.
+            Rule::Symbol { name } if kind.is_some() => {
+                let fields = match ctx.is_skipped(name) || ctx.is_hidden_token(name) {
+                    true => vec![],
+                    false => {
+                        let field = naming::field_ident(&name.to_case(Case::Snake));
+                        let field = ctx.field_name(ident, field).to_string();
+                        let ty = ctx.symbol_ref(name);
+                        vec![FieldDef { name: field, ty, cardinality: Cardinality::One }]
+                    }
+                };
+                Shape::Struct { fields }
+            }
+            Rule::Symbol { name } => Shape::Alias {
+                target: TypeRef::Type { name: ctx.rule_ident(name).to_string(), boxed: false },
+            },
+            Rule::Choice { members } => {
+                let mut variants: Vec<VariantDef> = vec![];
+                for (idx, item) in members.iter().enumerate() {
+                    let (name, members) = match item {
+                        Rule::String { value } => {
+                            let token = ctx.token(value);
+                            (token.clone(), vec![TypeRef::Token { name: token.to_string() }])
+                        }
+                        Rule::Symbol { name } if ctx.is_skipped(name) => continue,
+                        Rule::Symbol { name } if ctx.is_hidden_token(name) => {
+                            (ctx.rule_ident(name), vec![])
+                        }
+                        Rule::Symbol { name } => (ctx.rule_ident(name), vec![ctx.symbol_ref(name)]),
+                        Rule::Seq { members }
+                            if members.iter().all(|item| {
+                                matches!(item, Rule::String { .. } | Rule::Symbol { .. })
+                            }) =>
+                        {
+                            let members = members
+                                .iter()
+                                .filter_map(|item| match item {
+                                    Rule::String { value } => {
+                                        Some(TypeRef::Token { name: ctx.token(value).to_string() })
+                                    }
+                                    Rule::Symbol { name }
+                                        if !ctx.is_skipped(name) && !ctx.is_hidden_token(name) =>
+                                    {
+                                        Some(ctx.symbol_ref(name))
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+                            (ident!(&format!("Token{idx}")), members)
+                        }
+                        Rule::Alias { content, named: true, value } => {
+                            let field_type = alias_type(content, value, ctx, &mut res)?;
+                            (ident!(&value.to_case(Case::UpperCamel)), vec![field_type])
+                        }
+                        Rule::Alias { content: _, named: false, value } => {
+                            let token = ctx.token(value);
+                            (token.clone(), vec![TypeRef::Token { name: token.to_string() }])
+                        }
+                        Rule::Blank => (ident!("Blank"), vec![]),
+                        // Matches a token out of the tree, from which nothing is kept.
+                        Rule::Pattern { value: _, flags: _ } | Rule::Token { .. } => {
+                            (ident!(&format!("Token{idx}")), vec![])
+                        }
+                        Rule::Seq { members: _ }
+                        | Rule::Choice { members: _ }
+                        | Rule::Optional { content: _ } => {
+                            let field_type = nested_ident(ident, idx);
+                            res.extend(item.build(&field_type, ctx)?);
+                            (ident!(&format!("Token{idx}")), vec![ctx.nested_ref(&field_type)])
+                        }
+                        _ => {
+                            warn!("unhandled case for CHOICE: {item:?}");
+                            continue;
+                        }
+                    };
+
+                    let name = ctx.variant_name(ident, name).to_string();
+                    let variant = VariantDef { name, members };
+                    if !variants.contains(&variant) {
+                        variants.push(variant);
+                    }
+                }
+                Shape::Enum { variants: unique_variants(variants) }
+            }
+            // A field only names its content, which is the type of the rule.
+            Rule::Field { name: _, content } => {
+                ctx.set_kind(kind.as_deref());
+                return content.build(ident, ctx);
+            }
+            Rule::Seq { members } => {
+                let mut alls = HashSet::new();
+                let mut unique_name = |name: &str, idx: usize| {
+                    let mut name = name.to_case(Case::Snake);
+                    if alls.contains(&name) {
+                        name += &format!("_{idx}");
+                    } else {
+                        alls.insert(name.clone());
+                    }
+
+                    naming::field_ident(&name)
+                };
+
+                let mut fields = vec![];
+                for (idx, item) in members.iter().enumerate() {
+                    // A field only names its content, which is a member like any other.
+                    let (item, cardinality) = match field_content(item) {
+                        Rule::Optional { content } => {
+                            (field_content(content), Cardinality::Optional)
+                        }
+                        item => (item, Cardinality::One),
+                    };
+                    if ctx.is_out_of_tree(item) {
+                        continue;
+                    }
+                    let (name, ty, cardinality) = match item {
+                        Rule::Symbol { name } if ctx.is_skipped(name) => continue,
+                        Rule::Symbol { name } => {
+                            (unique_name(name, idx), ctx.symbol_ref(name), cardinality)
+                        }
+                        Rule::String { value } => {
+                            let token = ctx.token(value);
+                            let name = unique_name(&token.to_string(), idx);
+                            (name, TypeRef::Token { name: token.to_string() }, cardinality)
+                        }
+                        Rule::Repeat1 { content } | Rule::Repeat { content } => {
+                            let ty =
+                                element_ref(content, &nested_ident(ident, idx), ctx, &mut res)?;
+                            let name = match content.as_ref() {
+                                Rule::Symbol { name } if !ctx.is_skipped(name) => {
+                                    unique_name(name, idx)
+                                }
+                                _ => nested_field(ident, idx),
+                            };
+                            (name, ty, Cardinality::Many)
+                        }
+                        Rule::Choice { members: _ }
+                        | Rule::Seq { members: _ }
+                        | Rule::Optional { content: _ } => {
+                            let field_type = nested_ident(ident, idx);
+                            res.extend(item.build(&field_type, ctx)?);
+                            (nested_field(ident, idx), ctx.nested_ref(&field_type), cardinality)
+                        }
+                        Rule::Alias { content, named: true, value } => {
+                            let ty = alias_type(content, value, ctx, &mut res)?;
+                            (unique_name(value, idx), ty, cardinality)
+                        }
+                        Rule::Alias { content: _, named: false, value } => {
+                            let token = ctx.token(value);
+                            let name = unique_name(&token.to_string(), idx);
+                            (name, TypeRef::Token { name: token.to_string() }, cardinality)
+                        }
+                        _ => {
+                            warn!("unhandled case for SEQ: {item:?}");
+                            continue;
+                        }
+                    };
+
+                    let name = ctx.field_name(ident, name).to_string();
+                    fields.push(FieldDef { name, ty, cardinality });
+                }
+                Shape::Struct { fields }
+            }
+            Rule::Repeat1 { content } | Rule::Repeat { content } if ctx.is_out_of_tree(content) => {
+                Shape::Unit
+            }
+            Rule::Repeat1 { content } | Rule::Repeat { content } => {
+                let field_type = ident!(&format!("{}_TOKEN", ident).to_case(Case::UpperCamel));
+                let element = element_ref(content, &field_type, ctx, &mut res)?;
+                Shape::List { element }
+            }
+            Rule::Optional { content } => {
+                let mut members = match content.as_ref() {
+                    Rule::Choice { members } => members.clone(),
+                    content => vec![content.clone()],
+                };
+                members.push(Rule::Blank);
+                ctx.set_kind(kind.as_deref());
+                res.extend(Rule::Choice { members }.build(ident, ctx)?);
+                return Ok(res);
+            }
+        };
+
+        res.push(ctx.type_def(ident, kind, shape));
+        Ok(res)
+    }
+}
+
+/// Content of `rule` if it is a field, `rule` itself otherwise.
+fn field_content(rule: &Rule) -> &Rule {
+    match rule {
+        Rule::Field { content, .. } => content,
+        rule => rule,
+    }
+}
+
+/// Name of the type nested in `ident` for its member `idx`.
+fn nested_ident(ident: &Ident, idx: usize) -> Ident {
+    ident!(&format!("{ident}_TOKEN_{idx}").to_case(Case::UpperCamel))
+}
+
+/// Name of the field of `ident` holding its member `idx` when it has no better one.
+fn nested_field(ident: &Ident, idx: usize) -> Ident {
+    ident!(&format!("{ident}_TOKEN_{idx}").to_case(Case::Snake))
+}
+
+/// Type of the elements of a repetition of `content`, nested as `ident` unless it is a literal
+/// token or a rule, and never boxed behind the `Vec`.
+fn element_ref(
+    content: &Rule,
+    ident: &Ident,
+    ctx: &mut Context,
+    res: &mut Vec<TypeDef>,
+) -> anyhow::Result<TypeRef> {
+    let indirect = ctx.set_indirect(true);
+    let element = match content {
+        Rule::String { value } => TypeRef::Token { name: ctx.token(value).to_string() },
+        Rule::Symbol { name } if !ctx.is_skipped(name) => ctx.symbol_ref(name),
+        content => {
+            res.extend(content.build(ident, ctx)?);
+            ctx.nested_ref(ident)
+        }
+    };
+    ctx.set_indirect(indirect);
+    Ok(element)
+}
+
+/// Renames the variants sharing a name with a different content by numbering them.
+fn unique_variants(variants: Vec<VariantDef>) -> Vec<VariantDef> {
+    let mut res: Vec<VariantDef> = vec![];
+    for mut variant in variants {
+        let name = variant.name.clone();
+        let mut idx = 2;
+        while res.iter().any(|item| item.name == variant.name) {
+            variant.name = format!("{name}{idx}");
+            idx += 1;
+        }
+        res.push(variant);
+    }
+    res
+}
+
+/// Type of the nodes of `content` aliased as the named kind `value`.
+///
+/// Aliases to the kind of an existing rule reuse its type, others generate one type per kind
+/// from the first aliased content, parsing nodes of kind `value`.
+fn alias_type(
+    content: &Rule,
+    value: &str,
+    ctx: &mut Context,
+    res: &mut Vec<TypeDef>,
+) -> anyhow::Result<TypeRef> {
+    if ctx.rule(value).is_some() {
+        return Ok(ctx.symbol_ref(value));
+    }
+    if let Some(ident) = ctx.alias(value) {
+        return Ok(ctx.nested_ref(&ident));
+    }
+
+    let ident = ident!(&value.to_case(Case::UpperCamel));
+    ctx.insert_alias(value, ident.clone());
+
+    let mut content = content.clone();
+    while let Rule::Symbol { name } = &content {
+        match ctx.rule(name) {
+            Some(rule) => content = rule.clone(),
+            None => break,
+        }
+    }
+
+    ctx.set_kind(Some(value));
+    res.extend(content.build(&ident, ctx)?);
+    Ok(ctx.nested_ref(&ident))
+}
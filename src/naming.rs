@@ -0,0 +1,148 @@
+use convert_case::{Case, Casing};
+use syn::Ident;
+
+/// Names of punctuation sequences, multi-character operators are matched before single characters.
+const PUNCTUATIONS: &[(&str, &str)] = &[
+    ("<<=", "ShlEq"),
+    (">>=", "ShrEq"),
+    ("...", "DotDotDot"),
+    ("..=", "DotDotEq"),
+    ("===", "EqEqEq"),
+    ("!==", "NotEqEq"),
+    ("<=>", "Spaceship"),
+    ("::", "ColonColon"),
+    ("->", "Arrow"),
+    ("=>", "FatArrow"),
+    ("+=", "PlusEq"),
+    ("-=", "MinusEq"),
+    ("*=", "StarEq"),
+    ("/=", "SlashEq"),
+    ("%=", "PercentEq"),
+    ("^=", "CaretEq"),
+    ("&=", "AndEq"),
+    ("|=", "OrEq"),
+    ("==", "EqEq"),
+    ("!=", "NotEq"),
+    ("<=", "Le"),
+    (">=", "Ge"),
+    ("&&", "AndAnd"),
+    ("||", "OrOr"),
+    ("<<", "Shl"),
+    (">>", "Shr"),
+    ("++", "PlusPlus"),
+    ("--", "MinusMinus"),
+    ("**", "StarStar"),
+    ("..", "DotDot"),
+    ("??", "QuestionQuestion"),
+    ("?.", "QuestionDot"),
+    ("!", "Bang"),
+    ("\"", "DoubleQuote"),
+    ("#", "Hash"),
+    ("$", "Dollar"),
+    ("%", "Percent"),
+    ("&", "And"),
+    ("'", "SingleQuote"),
+    ("(", "LParen"),
+    (")", "RParen"),
+    ("*", "Star"),
+    ("+", "Plus"),
+    (",", "Comma"),
+    ("-", "Minus"),
+    (".", "Dot"),
+    ("/", "Slash"),
+    (":", "Colon"),
+    (";", "Semi"),
+    ("<", "Lt"),
+    ("=", "Eq"),
+    (">", "Gt"),
+    ("?", "Question"),
+    ("@", "At"),
+    ("[", "LBracket"),
+    ("\\", "Backslash"),
+    ("]", "RBracket"),
+    ("^", "Caret"),
+    ("_", "Underscore"),
+    ("`", "Backtick"),
+    ("{", "LBrace"),
+    ("|", "Or"),
+    ("}", "RBrace"),
+    ("~", "Tilde"),
+    (" ", "Space"),
+    ("\t", "Tab"),
+    ("\n", "Newline"),
+    ("\r", "CarriageReturn"),
+];
+
+/// Derives a type name from a literal token, e.g. `int16` → `Int16` and `->` → `Arrow`.
+pub(crate) fn token_name(value: &str) -> String {
+    let mut name = String::new();
+    let mut rest = value;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_alphanumeric() {
+            let end =
+                rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+            name += &(&rest[..end]).to_case(Case::UpperCamel);
+            rest = &rest[end..];
+        } else if let Some((symbol, symbol_name)) = PUNCTUATIONS
+            .iter()
+            .filter(|(symbol, _)| rest.starts_with(symbol))
+            .max_by_key(|(symbol, _)| symbol.len())
+        {
+            name += symbol_name;
+            rest = &rest[symbol.len()..];
+        } else {
+            name += &format!("U{:X}", c as u32);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if !name.starts_with(|c: char| c.is_ascii_alphabetic()) || name == "Self" {
+        format!("Token{name}")
+    } else {
+        name
+    }
+}
+
+/// Variant identifiers of an enum listing `values`, numbering the ones whose names clash.
+pub(crate) fn variant_idents(values: &[String]) -> Vec<Ident> {
+    let mut res: Vec<Ident> = vec![];
+    for value in values {
+        let name = token_name(value);
+        let mut ident = ident!(&name);
+        let mut idx = 2;
+        while res.contains(&ident) {
+            ident = ident!(&format!("{name}{idx}"));
+            idx += 1;
+        }
+        res.push(ident);
+    }
+    res
+}
+
+/// Converts `name` into a field identifier, escaping Rust keywords with a trailing underscore.
+pub(crate) fn field_ident(name: &str) -> Ident {
+    let name = name.to_case(Case::Snake);
+    match syn::parse_str::<Ident>(&name) {
+        Ok(_) => ident!(&name),
+        Err(_) => ident!(&format!("{name}_")),
+    }
+}
+
+/// Converts the rule `name` into a module identifier not clashing with the fixed modules.
+pub(crate) fn module_ident(name: &str) -> Ident {
+    let ident = field_ident(name);
+    if RESERVED_MODULES.contains(&ident.to_string().as_str()) {
+        ident!(&format!("{ident}_"))
+    } else {
+        ident
+    }
+}
+
+/// Module of the types generated for external tokens.
+pub(crate) const EXTERNALS: &str = "externals";
+
+/// Modules always present in split output.
+pub(crate) const RESERVED_MODULES: &[&str] = &["tokens", EXTERNALS];
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+
+use indexmap::IndexSet;
+use log::*;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+
+use crate::{naming, AstModel, Cardinality, Context, Grammar, Rule, Shape, TypeDef, TypeRef};
+
+/// Named node kinds of `grammar`: its visible rules and external tokens, then the values of its
+/// named aliases, in grammar order.
+pub(crate) fn node_kinds(grammar: &Grammar) -> Vec<String> {
+    let mut res = IndexSet::new();
+    res.extend(grammar.rules.keys().map(String::as_str));
+    for item in &grammar.externals {
+        if let Rule::Symbol { name } = item {
+            res.insert(name);
+        }
+    }
+    for rule in grammar.rules.values() {
+        aliases(rule, &mut res);
+    }
+    res.into_iter().filter(|name| !name.starts_with('_')).map(str::to_string).collect()
+}
+
+/// Collects the values of the named aliases in `rule`.
+fn aliases<'a>(rule: &'a Rule, res: &mut IndexSet<&'a str>) {
+    match rule {
+        Rule::Alias { content, named, value } => {
+            if *named {
+                res.insert(value);
+            }
+            aliases(content, res);
+        }
+        Rule::Choice { members } | Rule::Seq { members } => {
+            members.iter().for_each(|item| aliases(item, res))
+        }
+        Rule::Field { content, .. }
+        | Rule::Optional { content }
+        | Rule::Repeat { content }
+        | Rule::Repeat1 { content }
+        | Rule::Token { content, .. } => aliases(content, res),
+        Rule::Blank | Rule::String { .. } | Rule::Pattern { .. } | Rule::Symbol { .. } => {}
+    }
+}
+
+/// Names a generated type may not have for the node enums and the `AstNode` trait to be
+/// generated.
+const RESERVED: &[&str] = &["NodeKind", "AnyNode", "AnyNodeRef", "AstNode"];
+
+/// Generates the enums of the node kinds and of the generated types, and the `AstNode` trait
+/// walking the values of any type.
+pub(crate) struct Nodes<'a> {
+    model: &'a AstModel,
+    types: HashMap<&'a str, &'a TypeDef>,
+    /// `NodeKind` variant of each named node kind.
+    kinds: HashMap<&'a str, Ident>,
+    /// Types of `AnyNode`, the ones which are not aliases.
+    values: IndexSet<&'a str>,
+    /// Whether a generated type has a reserved name, so nothing is generated.
+    reserved: bool,
+}
+
+impl<'a> Nodes<'a> {
+    pub(crate) fn new(model: &'a AstModel) -> Self {
+        let reserved = model.types.iter().find(|def| RESERVED.contains(&def.name.as_str()));
+        if let Some(def) = reserved {
+            warn!("a rule is named `{}`, no node enums are generated", def.name);
+        }
+        Self {
+            model,
+            types: model.types.iter().map(|def| (def.name.as_str(), def)).collect(),
+            kinds: model
+                .kinds
+                .iter()
+                .map(String::as_str)
+                .zip(naming::variant_idents(&model.kinds))
+                .collect(),
+            values: model
+                .types
+                .iter()
+                .filter(|def| !matches!(def.shape, Shape::Alias { .. }))
+                .map(|def| def.name.as_str())
+                .collect(),
+            reserved: reserved.is_some(),
+        }
+    }
+
+    /// The `NodeKind` enum listing the node kinds of the model, the `AnyNode` and `AnyNodeRef`
+    /// enums holding any of its types and the `AstNode` trait they implement.
+    pub(crate) fn enums(&self, ctx: &Context) -> TokenStream {
+        if self.reserved {
+            return quote! {};
+        }
+
+        let model = self.model;
+        let kinds = naming::variant_idents(&model.kinds);
+        let values: Vec<_> = model.kinds.iter().map(|value| lit_str!(value)).collect();
+        let derives = ctx.shared_derives(&[
+            "Debug",
+            "Clone",
+            "Copy",
+            "PartialEq",
+            "Eq",
+            "PartialOrd",
+            "Ord",
+            "Hash",
+        ]);
+
+        let types: Vec<_> = self.values.iter().map(|name| ident!(name)).collect();
+        let types = &types;
+        // A kind is converted into the first type parsed from its nodes.
+        let arms = model.kinds.iter().zip(&kinds).filter_map(|(kind, variant)| {
+            let def = model.types.iter().find(|def| {
+                def.kind.as_ref() == Some(kind) && !matches!(def.shape, Shape::Alias { .. })
+            })?;
+            let ident = ident!(&def.name);
+            Some(quote! {
+                Some(NodeKind::#variant) => #ident::parse(node, source).map(AnyNode::#ident),
+            })
+        });
+        let type_derives = ctx.type_derives();
+
+        quote! {
+            /// Named node kinds of the grammar, from its rules and aliases.
+            #[derive(#(#derives),*)]
+            pub enum NodeKind {
+                #(#kinds,)*
+            }
+
+            impl NodeKind {
+                pub const ALL: &'static [NodeKind] = &[#(NodeKind::#kinds),*];
+
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        #(NodeKind::#kinds => #values,)*
+                    }
+                }
+
+                /// The kind named `name`, none if the grammar has no such named node.
+                pub fn from_name(name: &str) -> Option<Self> {
+                    match name {
+                        #(#values => Some(NodeKind::#kinds),)*
+                        _ => None,
+                    }
+                }
+
+                /// Kind of `node`, none for unnamed nodes.
+                pub fn of(node: TSNode<'_>) -> Option<Self> {
+                    match node.is_named() {
+                        true => Self::from_name(node.kind()),
+                        false => None,
+                    }
+                }
+            }
+
+            /// A value of any type generated for the grammar.
+            #[derive(#(#type_derives),*)]
+            pub enum AnyNode {
+                #(#types(#types),)*
+            }
+
+            impl AnyNode {
+                /// Converts `node` into the type generated for its kind.
+                pub fn from_ts_node(node: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
+                    match NodeKind::of(node) {
+                        #(#arms)*
+                        _ => Err(ParseError::unexpected(node)),
+                    }
+                }
+
+                pub fn as_node_ref(&self) -> AnyNodeRef<'_> {
+                    match self {
+                        #(AnyNode::#types(item) => AnyNodeRef::#types(item),)*
+                    }
+                }
+            }
+
+            impl AstNode for AnyNode {
+                fn kind(&self) -> Option<NodeKind> {
+                    self.as_node_ref().kind()
+                }
+
+                fn span(&self) -> Option<Span> {
+                    self.as_node_ref().span()
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    self.as_node_ref().children()
+                }
+
+                fn downcast_ref<T: AstNode>(&self) -> Option<&T> {
+                    self.as_node_ref().downcast_ref()
+                }
+            }
+
+            /// A reference to a value of any type generated for the grammar.
+            #[derive(Debug, Clone, Copy)]
+            pub enum AnyNodeRef<'a> {
+                #(#types(&'a #types),)*
+            }
+
+            impl<'a> AnyNodeRef<'a> {
+                pub fn kind(self) -> Option<NodeKind> {
+                    match self {
+                        #(AnyNodeRef::#types(item) => item.kind(),)*
+                    }
+                }
+
+                pub fn span(self) -> Option<Span> {
+                    match self {
+                        #(AnyNodeRef::#types(item) => item.span(),)*
+                    }
+                }
+
+                pub fn children(self) -> impl Iterator<Item = AnyNodeRef<'a>> {
+                    let res: Vec<_> = match self {
+                        #(AnyNodeRef::#types(item) => item.children().collect(),)*
+                    };
+                    res.into_iter()
+                }
+
+                /// The referenced value if it is a `T`.
+                pub fn downcast_ref<T: AstNode>(self) -> Option<&'a T> {
+                    match self {
+                        #(AnyNodeRef::#types(item) => (item as &dyn std::any::Any).downcast_ref(),)*
+                    }
+                }
+            }
+
+            /// A value of a type generated for a rule or an external of the grammar, walked the
+            /// same way whatever its type. The unit types of literal tokens are not part of it.
+            pub trait AstNode: std::any::Any {
+                /// Kind of the node the value is parsed from, none for hidden rules and nested
+                /// types.
+                fn kind(&self) -> Option<NodeKind>;
+
+                /// Source of the node the value is parsed from, kept when parsing it. Values of
+                /// hidden rules and nested types have no node and join the spans of their
+                /// children, none without any.
+                fn span(&self) -> Option<Span>;
+
+                /// Values of generated types directly held by the value, in source order.
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>>;
+
+                /// The value if it is a `T`.
+                fn downcast_ref<T: AstNode>(&self) -> Option<&T>
+                where
+                    Self: Sized,
+                {
+                    (self as &dyn std::any::Any).downcast_ref()
+                }
+            }
+        }
+    }
+
+    /// The `AnyNodeRef` variant referring to a value of `ty`, none for tokens and user types.
+    fn variant(&self, ty: &TypeRef) -> Option<Ident> {
+        let TypeRef::Type { name, .. } = ty else {
+            return None;
+        };
+        let def = *self.types.get(name.as_str())?;
+        match &def.shape {
+            Shape::Alias { target } => self.variant(target),
+            _ => Some(ident!(&def.name)),
+        }
+    }
+
+    /// `AstNode` implementation of `def`.
+    pub(crate) fn type_impl(&self, def: &TypeDef) -> TokenStream {
+        if self.reserved || !self.values.contains(def.name.as_str()) {
+            return quote! {};
+        }
+
+        let ident = ident!(&def.name);
+        let kind = match def.kind.as_deref().and_then(|kind| self.kinds.get(kind)) {
+            Some(variant) => quote! { Some(NodeKind::#variant) },
+            None => quote! { None },
+        };
+        let push = |variant: &Ident, value: TokenStream| -> TokenStream {
+            quote! { res.push(AnyNodeRef::#variant(#value)); }
+        };
+        // The children always held come first and start the vector, the others are pushed.
+        let mut first = vec![];
+        let mut rest = vec![];
+        match &def.shape {
+            Shape::Struct { fields } => {
+                for field in fields {
+                    let Some(variant) = self.variant(&field.ty) else {
+                        continue;
+                    };
+                    let name = ident!(&field.name);
+                    match field.cardinality {
+                        Cardinality::One if rest.is_empty() => {
+                            first.push(quote! { AnyNodeRef::#variant(&self.#name) })
+                        }
+                        Cardinality::One => rest.push(push(&variant, quote! { &self.#name })),
+                        Cardinality::Optional => {
+                            let push = push(&variant, quote! { item });
+                            rest.push(quote! {
+                                if let Some(item) = &self.#name {
+                                    #push
+                                }
+                            });
+                        }
+                        Cardinality::Many => {
+                            let push = push(&variant, quote! { item });
+                            rest.push(quote! {
+                                for item in &self.#name {
+                                    #push
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+            Shape::List { element } => {
+                if let Some(variant) = self.variant(element) {
+                    let push = push(&variant, quote! { item });
+                    rest.push(quote! {
+                        for item in &self.value {
+                            #push
+                        }
+                    });
+                }
+            }
+            Shape::Enum { variants } => {
+                let mut pushed = false;
+                let arms: Vec<_> = variants
+                    .iter()
+                    .map(|variant| {
+                        let name = ident!(&variant.name);
+                        let mut pushes = vec![];
+                        let mut items: Vec<_> = variant
+                            .fields()
+                            .enumerate()
+                            .map(|(idx, item)| match self.variant(item) {
+                                Some(variant) => {
+                                    let binding = ident!(&format!("item{idx}"));
+                                    pushes.push(push(&variant, quote! { #binding }));
+                                    quote! { #binding }
+                                }
+                                None => quote! { _ },
+                            })
+                            .collect();
+                        items.extend(def.has_span().then(|| quote! { _ }));
+                        pushed |= !pushes.is_empty();
+                        match (items.is_empty(), pushes.is_empty()) {
+                            (true, _) => quote! { Self::#name => {} },
+                            (false, true) => quote! { Self::#name(..) => {} },
+                            (false, false) => {
+                                quote! { Self::#name(#(#items),*) => { #(#pushes)* } }
+                            }
+                        }
+                    })
+                    .collect();
+                if pushed {
+                    rest.push(quote! { match self { #(#arms)* } });
+                }
+            }
+            Shape::Unit | Shape::Leaf | Shape::Alias { .. } => {}
+        }
+        let children = match (first.is_empty(), rest.is_empty()) {
+            (true, true) => quote! { std::iter::empty() },
+            (false, true) => quote! { vec![#(#first),*].into_iter() },
+            (_, false) => quote! {
+                let mut res: Vec<AnyNodeRef<'_>> = vec![#(#first),*];
+                #(#rest)*
+                res.into_iter()
+            },
+        };
+        let span = match &def.shape {
+            Shape::Enum { variants } if def.has_span() => {
+                let names = variants.iter().map(|variant| ident!(&variant.name));
+                quote! {
+                    match self {
+                        #(Self::#names(.., span) => Some(*span),)*
+                    }
+                }
+            }
+            _ if def.has_span() => quote! { Some(self.span) },
+            Shape::Unit => quote! { None },
+            _ => quote! { self.children().filter_map(|child| child.span()).reduce(Span::join) },
+        };
+
+        quote! {
+            impl AstNode for #ident {
+                fn kind(&self) -> Option<NodeKind> {
+                    #kind
+                }
+
+                fn span(&self) -> Option<Span> {
+                    #span
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    #children
+                }
+            }
+        }
+    }
+}
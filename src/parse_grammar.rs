@@ -1,9 +1,8 @@
 #![allow(unused_variables)]
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 
-use serde::Deserialize;
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
@@ -11,7 +10,11 @@ pub enum RuleJSON {
     ALIAS { content: Box<RuleJSON>, named: bool, value: String },
     BLANK,
     STRING { value: String },
-    PATTERN { value: String, flags: Option<String> },
+    PATTERN {
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        flags: Option<String>,
+    },
     SYMBOL { name: String },
     CHOICE { members: Vec<RuleJSON> },
     FIELD { name: String, content: Box<RuleJSON> },
@@ -26,28 +29,85 @@ pub enum RuleJSON {
     IMMEDIATE_TOKEN { content: Box<RuleJSON> },
 }
 
-#[derive(Debug, Deserialize)]
+impl RuleJSON {
+    /// The `type` of the rule in grammar.json.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RuleJSON::ALIAS { .. } => "ALIAS",
+            RuleJSON::BLANK => "BLANK",
+            RuleJSON::STRING { .. } => "STRING",
+            RuleJSON::PATTERN { .. } => "PATTERN",
+            RuleJSON::SYMBOL { .. } => "SYMBOL",
+            RuleJSON::CHOICE { .. } => "CHOICE",
+            RuleJSON::FIELD { .. } => "FIELD",
+            RuleJSON::SEQ { .. } => "SEQ",
+            RuleJSON::REPEAT { .. } => "REPEAT",
+            RuleJSON::REPEAT1 { .. } => "REPEAT1",
+            RuleJSON::PREC_DYNAMIC { .. } => "PREC_DYNAMIC",
+            RuleJSON::PREC_LEFT { .. } => "PREC_LEFT",
+            RuleJSON::PREC_RIGHT { .. } => "PREC_RIGHT",
+            RuleJSON::PREC { .. } => "PREC",
+            RuleJSON::TOKEN { .. } => "TOKEN",
+            RuleJSON::IMMEDIATE_TOKEN { .. } => "IMMEDIATE_TOKEN",
+        }
+    }
+
+    /// Visits the rule and every rule it contains, parents first.
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a RuleJSON)) {
+        f(self);
+        match self {
+            RuleJSON::CHOICE { members } | RuleJSON::SEQ { members } => {
+                members.iter().for_each(|item| item.visit(f))
+            }
+            RuleJSON::ALIAS { content, .. }
+            | RuleJSON::FIELD { content, .. }
+            | RuleJSON::REPEAT { content }
+            | RuleJSON::REPEAT1 { content }
+            | RuleJSON::PREC_DYNAMIC { content, .. }
+            | RuleJSON::PREC_LEFT { content, .. }
+            | RuleJSON::PREC_RIGHT { content, .. }
+            | RuleJSON::PREC { content, .. }
+            | RuleJSON::TOKEN { content }
+            | RuleJSON::IMMEDIATE_TOKEN { content } => content.visit(f),
+            RuleJSON::BLANK
+            | RuleJSON::STRING { .. }
+            | RuleJSON::PATTERN { .. }
+            | RuleJSON::SYMBOL { .. } => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PrecedenceValueJSON {
     Integer(i32),
     Name(String),
 }
 
-#[derive(Debug, Deserialize)]
+/// A grammar.json, serialized back with its fields and rules in the order tree-sitter writes.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GrammarJSON {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
     pub name: String,
-    pub rules: HashMap<String, RuleJSON>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherits: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+    /// Rules in grammar order, the first one is the start rule.
+    pub rules: IndexMap<String, RuleJSON>,
     #[serde(default)]
-    pub precedences: Vec<Vec<RuleJSON>>,
+    pub extras: Vec<RuleJSON>,
     #[serde(default)]
     pub conflicts: Vec<Vec<String>>,
     #[serde(default)]
-    pub externals: Vec<RuleJSON>,
+    pub precedences: Vec<Vec<RuleJSON>>,
     #[serde(default)]
-    pub extras: Vec<RuleJSON>,
+    pub externals: Vec<RuleJSON>,
     #[serde(default)]
     pub inline: Vec<String>,
     #[serde(default)]
     pub supertypes: Vec<String>,
-    pub word: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub reserved: IndexMap<String, Vec<RuleJSON>>,
 }
@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexSet;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::{Cardinality, Shape, TokenDef, TypeDef, TypeRef};
+
+/// Generates the `TSParser` implementations of the types of a model.
+///
+/// Node kinds are checked by comparing their names, or with the language of the grammar by
+/// comparing their numeric ids, resolved once into the generated `kind_ids` table.
+pub(crate) struct Parsers<'a> {
+    types: HashMap<&'a str, &'a TypeDef>,
+    /// Named and unnamed kinds in the order of the `kind_ids` table.
+    kinds: Option<IndexSet<(&'a str, bool)>>,
+}
+
+impl<'a> Parsers<'a> {
+    pub(crate) fn new(types: &'a [TypeDef], tokens: &'a [TokenDef], kind_ids: bool) -> Self {
+        let kinds = kind_ids.then(|| {
+            let named = types
+                .iter()
+                .filter(|def| !matches!(def.shape, Shape::Alias { .. }))
+                .filter_map(|def| Some((def.kind.as_deref()?, true)));
+            named.chain(tokens.iter().map(|token| (token.value.as_str(), false))).collect()
+        });
+
+        Self { types: types.iter().map(|def| (def.name.as_str(), def)).collect(), kinds }
+    }
+
+    /// The `kind_ids` table, resolving every checked kind with the `language` function.
+    pub(crate) fn kind_table(&self, language: &syn::Path) -> TokenStream {
+        let Some(kinds) = self.kinds.as_ref().filter(|kinds| !kinds.is_empty()) else {
+            return quote! {};
+        };
+
+        let len = Literal::usize_unsuffixed(kinds.len());
+        let ids = kinds.iter().map(|(kind, named)| {
+            quote! { language.id_for_node_kind(#kind, #named) }
+        });
+        quote! {
+            /// Ids of the node kinds of the grammar, resolved from its language on first use.
+            fn kind_ids() -> &'static [u16; #len] {
+                static KIND_IDS: std::sync::OnceLock<[u16; #len]> = std::sync::OnceLock::new();
+                KIND_IDS.get_or_init(|| {
+                    let language = #language();
+                    [#(#ids),*]
+                })
+            }
+        }
+    }
+
+    /// Whether `node` is of `kind`, failing if it is not in the `kind_ids` table.
+    fn is_kind(&self, kind: &str, named: bool) -> anyhow::Result<TokenStream> {
+        let Some(kinds) = &self.kinds else {
+            return Ok(quote! { node.kind() == #kind });
+        };
+        let Some(idx) = kinds.get_index_of(&(kind, named)) else {
+            anyhow::bail!("node kind `{kind}` has no id in the kind table");
+        };
+        let idx = Literal::usize_unsuffixed(idx);
+        Ok(quote! { node.kind_id() == kind_ids()[#idx] })
+    }
+
+    pub(crate) fn token_impl(&self, token: &TokenDef) -> anyhow::Result<TokenStream> {
+        let ident = ident!(&token.name);
+        self.node_impl(&ident, &token.value, false, None)
+    }
+
+    /// `TSParser` implementation of `def`, none for aliases.
+    pub(crate) fn type_impl(&self, def: &TypeDef) -> anyhow::Result<TokenStream> {
+        let ident = ident!(&def.name);
+        let span = def.has_span().then(|| quote! { span: Span::of(node), });
+        let content = match &def.shape {
+            Shape::Alias { .. } => return Ok(quote! {}),
+            Shape::Unit | Shape::Leaf => match &def.kind {
+                Some(kind) => {
+                    let body = matches!(def.shape, Shape::Leaf).then(|| leaf(kind));
+                    return self.node_impl(&ident, kind, true, body);
+                }
+                // Only units of tokens out of the tree have no kind, leaves always have one.
+                None => quote! { Self },
+            },
+            Shape::Struct { fields } => {
+                let fields = fields.iter().map(|field| {
+                    let name = ident!(&field.name);
+                    match field.cardinality {
+                        Cardinality::One => quote! { #name: children.parse(source)?, },
+                        Cardinality::Optional => quote! { #name: children.parse_if(source)?, },
+                        Cardinality::Many => quote! { #name: children.parse_while(source)?, },
+                    }
+                });
+                quote! { Self { #(#fields)* #span } }
+            }
+            Shape::Enum { variants } => {
+                let alternatives = variants.iter().map(|variant| {
+                    let name = ident!(&variant.name);
+                    let first = self.first_types(variant.members.iter().map(|item| (item, false)));
+                    let starts_with = match first.as_slice() {
+                        [] => quote! { |_| false },
+                        [ty] => quote! { <#ty as TSParser>::starts_with },
+                        types => quote! { |node| #(<#types as TSParser>::starts_with(node))||* },
+                    };
+                    let nullable = variant.members.iter().all(|item| self.nullable(item));
+
+                    let mut fields = vec![];
+                    let members = variant.members.iter().map(|item| match item {
+                        TypeRef::Token { .. } => {
+                            let ty = item.to_tokens();
+                            quote! { children.parse::<#ty>(source)?; }
+                        }
+                        _ => {
+                            let field = ident!(&format!("item{}", fields.len()));
+                            fields.push(field.clone());
+                            quote! { let #field = children.parse(source)?; }
+                        }
+                    });
+                    let members: Vec<_> = members.collect();
+                    let mut items: Vec<_> = fields.iter().map(|field| quote! { #field }).collect();
+                    items.extend(def.has_span().then(|| quote! { Span::of(children.parent()) }));
+                    let value = match items.is_empty() {
+                        true => quote! { Self::#name },
+                        false => quote! { Self::#name(#(#items),*) },
+                    };
+                    let parse = match (members.is_empty(), def.has_span()) {
+                        (true, false) => quote! { |_, _| Ok(#value) },
+                        (true, true) => quote! { |children, _| Ok(#value) },
+                        (false, _) => quote! { |children, source| { #(#members)* Ok(#value) } },
+                    };
+
+                    quote! {
+                        Alternative {
+                            starts_with: #starts_with,
+                            nullable: #nullable,
+                            parse: #parse,
+                        },
+                    }
+                });
+                // Errors name the type of nested choices, which have no kind.
+                let expected = def.kind.clone().unwrap_or_else(|| def.name.clone());
+                quote! { children.choose(&[#(#alternatives)*], #expected, source) }
+            }
+            Shape::List { .. } => {
+                quote! { Self { value: children.parse_while(source)?, #span } }
+            }
+        };
+
+        // Alternatives are chosen by a fallible call, other types are built field by field.
+        let result = match &def.shape {
+            Shape::Enum { .. } => content,
+            _ => quote! { Ok(#content) },
+        };
+        match &def.kind {
+            Some(kind) => self.node_impl(
+                &ident,
+                kind,
+                true,
+                Some(match def.shape {
+                    // Alternatives are plain functions, which take the node from the children.
+                    Shape::Enum { .. } => quote! {
+                        children.node(#kind, Self::starts_with, |_, children| #result)
+                    },
+                    _ => quote! {
+                        children.node(#kind, Self::starts_with, |node, children| #result)
+                    },
+                }),
+            ),
+            None => {
+                let first = match &def.shape {
+                    Shape::Struct { fields } => self.first_types(
+                        fields
+                            .iter()
+                            .map(|field| (&field.ty, field.cardinality != Cardinality::One)),
+                    ),
+                    Shape::Enum { variants } => variants
+                        .iter()
+                        .flat_map(|variant| {
+                            self.first_types(variant.members.iter().map(|item| (item, false)))
+                        })
+                        .collect(),
+                    Shape::List { element } => vec![element.to_tokens()],
+                    _ => vec![],
+                };
+                let mut seen = HashSet::new();
+                let first: Vec<_> =
+                    first.into_iter().filter(|ty| seen.insert(ty.to_string())).collect();
+                let starts_with = match first.is_empty() {
+                    true => quote! {
+                        fn starts_with(_: TSNode<'_>) -> bool {
+                            false
+                        }
+                    },
+                    false => quote! {
+                        fn starts_with(node: TSNode<'_>) -> bool {
+                            #(<#first as TSParser>::starts_with(node))||*
+                        }
+                    },
+                };
+
+                Ok(quote! {
+                    impl TSParser for #ident {
+                        #starts_with
+
+                        fn parse_children(
+                            children: &mut Children<'_, '_>,
+                            source: &[u8],
+                        ) -> ParseResult<Self> {
+                            #result
+                        }
+                    }
+                })
+            }
+        }
+    }
+
+    /// Implementation for a type built from a single node of `kind`, with `body` consuming it
+    /// from the children or a unit type without it, keeping the span of named nodes.
+    fn node_impl(
+        &self,
+        ident: &syn::Ident,
+        kind: &str,
+        named: bool,
+        body: Option<TokenStream>,
+    ) -> anyhow::Result<TokenStream> {
+        let is_kind = self.is_kind(kind, named)?;
+        let (source, body) = match body {
+            Some(body) => (quote! { source }, body),
+            None if named => (
+                quote! { _source },
+                quote! {
+                    let node = children.leaf(#kind, Self::starts_with)?;
+                    Ok(Self { span: Span::of(node) })
+                },
+            ),
+            None => (
+                quote! { _source },
+                quote! {
+                    children.leaf(#kind, Self::starts_with)?;
+                    Ok(Self)
+                },
+            ),
+        };
+        Ok(quote! {
+            impl TSParser for #ident {
+                fn starts_with(node: TSNode<'_>) -> bool {
+                    #is_kind
+                }
+
+                fn parse_children(
+                    children: &mut Children<'_, '_>,
+                    #source: &[u8],
+                ) -> ParseResult<Self> {
+                    #body
+                }
+            }
+        })
+    }
+
+    /// Types whose first node may start a sequence of `members`, each with whether it may be
+    /// left out.
+    fn first_types<'b>(
+        &self,
+        members: impl Iterator<Item = (&'b TypeRef, bool)>,
+    ) -> Vec<TokenStream> {
+        let mut res = vec![];
+        for (item, optional) in members {
+            res.push(item.to_tokens());
+            if !optional && !self.nullable(item) {
+                break;
+            }
+        }
+        res
+    }
+
+    /// Whether `ty` may be built from no node at all.
+    fn nullable(&self, ty: &TypeRef) -> bool {
+        self.nullable_in(ty, &mut vec![])
+    }
+
+    fn nullable_in(&self, ty: &TypeRef, visiting: &mut Vec<String>) -> bool {
+        let TypeRef::Type { name, .. } = ty else {
+            return false;
+        };
+        let Some(def) = self.types.get(name.as_str()) else {
+            return false;
+        };
+        if let Shape::Alias { target } = &def.shape {
+            return self.nullable_in(target, visiting);
+        }
+        if def.kind.is_some() || visiting.contains(name) {
+            return false;
+        }
+
+        visiting.push(name.clone());
+        let res = match &def.shape {
+            Shape::Unit | Shape::Alias { .. } => false,
+            Shape::Leaf | Shape::List { .. } => true,
+            Shape::Struct { fields } => fields.iter().all(|field| {
+                field.cardinality != Cardinality::One || self.nullable_in(&field.ty, visiting)
+            }),
+            Shape::Enum { variants } => variants
+                .iter()
+                .any(|variant| variant.members.iter().all(|item| self.nullable_in(item, visiting))),
+        };
+        visiting.pop();
+        res
+    }
+}
+
+/// Builds a leaf from the text of the next node of `kind`.
+fn leaf(kind: &str) -> TokenStream {
+    quote! {
+        let root = children.leaf(#kind, Self::starts_with)?;
+        Ok(Self { value: utf8_text(root, source)?.to_string(), span: Span::of(root) })
+    }
+}
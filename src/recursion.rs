@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Config, Grammar, Rule};
+
+/// Finds the references between the rules of `grammar` which need a `Box` so that no type
+/// contains itself by value, directly or through other rules.
+///
+/// Rules are walked depth first in name order and every reference back to a rule still being
+/// walked closes a cycle, so boxing those alone breaks every cycle with a single indirection.
+/// Rules skipped or replaced by a user type in `config` are not generated and so break every
+/// cycle.
+pub(crate) fn boxed_references(grammar: &Grammar, config: &Config) -> HashSet<(String, String)> {
+    let is_generated = |name: &str| {
+        config.rules.get(name).is_none_or(|rule| !rule.skip && rule.type_path.is_none())
+    };
+    let mut names: Vec<_> = grammar.rules.keys().filter(|name| is_generated(name)).collect();
+    names.sort();
+    let index: HashMap<_, _> =
+        names.iter().enumerate().map(|(idx, name)| (name.as_str(), idx)).collect();
+
+    let edges: Vec<Vec<usize>> = names
+        .iter()
+        .map(|name| {
+            let mut symbols = vec![];
+            value_symbols(grammar, &grammar.rules[*name], &mut symbols);
+            symbols.iter().filter_map(|item| index.get(item).copied()).collect()
+        })
+        .collect();
+
+    let mut walk = Walk {
+        edges: &edges,
+        state: vec![State::New; names.len()],
+        back: HashSet::new(),
+    };
+    for node in 0..names.len() {
+        if walk.state[node] == State::New {
+            walk.visit(node);
+        }
+    }
+
+    walk.back.into_iter().map(|(from, to)| (names[from].clone(), names[to].clone())).collect()
+}
+
+/// Collects the rules referenced by `rule` that are stored inline rather than behind a `Vec`.
+fn value_symbols<'a>(grammar: &'a Grammar, rule: &'a Rule, res: &mut Vec<&'a str>) {
+    match rule {
+        Rule::Symbol { name } => res.push(name),
+        Rule::Choice { members } | Rule::Seq { members } => {
+            members.iter().for_each(|item| value_symbols(grammar, item, res))
+        }
+        Rule::Alias { value, named: true, .. } if grammar.rules.contains_key(value) => {
+            res.push(value)
+        }
+        Rule::Alias { content, named: true, .. } => value_symbols(grammar, content, res),
+        Rule::Field { content, .. } | Rule::Optional { content } => {
+            value_symbols(grammar, content, res)
+        }
+        Rule::Alias { named: false, .. }
+        | Rule::Token { .. }
+        | Rule::Repeat { .. }
+        | Rule::Repeat1 { .. }
+        | Rule::Blank
+        | Rule::String { .. }
+        | Rule::Pattern { .. } => {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    New,
+    /// Being walked, on the path from the rule the walk started at.
+    Open,
+    Done,
+}
+
+struct Walk<'a> {
+    edges: &'a [Vec<usize>],
+    state: Vec<State>,
+    /// References to a rule being walked.
+    back: HashSet<(usize, usize)>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, node: usize) {
+        self.state[node] = State::Open;
+        for &next in &self.edges[node] {
+            match self.state[next] {
+                State::New => self.visit(next),
+                State::Open => {
+                    self.back.insert((node, next));
+                }
+                State::Done => {}
+            }
+        }
+        self.state[node] = State::Done;
+    }
+}
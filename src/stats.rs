@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{Config, GrammarJSON};
+
+/// Number of largest rules listed by [`GrammarJSON::stats`].
+const LARGEST: usize = 10;
+
+/// Size of a grammar and of the code generated from it.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    /// Number of rules by the `type` of their body.
+    pub rules: BTreeMap<String, usize>,
+    /// Number of generated items by kind: `struct`, `enum` and `type`.
+    pub types: BTreeMap<String, usize>,
+    /// The largest rules with the number of nodes in their body, largest first.
+    pub largest: Vec<(String, usize)>,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rules: {}", self.rules.values().sum::<usize>())?;
+        for (kind, count) in &self.rules {
+            writeln!(f, "  {kind}: {count}")?;
+        }
+        writeln!(f, "generated types: {}", self.types.values().sum::<usize>())?;
+        for (kind, count) in &self.types {
+            writeln!(f, "  {kind}: {count}")?;
+        }
+        writeln!(f, "largest rules:")?;
+        for (name, size) in &self.largest {
+            writeln!(f, "  {name}: {size}")?;
+        }
+        Ok(())
+    }
+}
+
+impl GrammarJSON {
+    /// Counts the rules of the grammar and the types generated from it with `config`.
+    pub fn stats(&self, config: &Config) -> anyhow::Result<Stats> {
+        let mut res = Stats::default();
+        for rule in self.rules.values() {
+            *res.rules.entry(rule.type_name().to_string()).or_default() += 1;
+        }
+
+        let mut items = self.to_file_with(config)?.items;
+        while let Some(item) = items.pop() {
+            let kind = match item {
+                syn::Item::Struct(_) => "struct",
+                syn::Item::Enum(_) => "enum",
+                syn::Item::Type(_) => "type",
+                syn::Item::Mod(item) => {
+                    items.extend(item.content.map(|(_, items)| items).unwrap_or_default());
+                    continue;
+                }
+                _ => continue,
+            };
+            *res.types.entry(kind.to_string()).or_default() += 1;
+        }
+
+        let mut sizes: Vec<_> = self
+            .rules
+            .iter()
+            .map(|(name, rule)| {
+                let mut size = 0;
+                rule.visit(&mut |_| size += 1);
+                (name.clone(), size)
+            })
+            .collect();
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sizes.truncate(LARGEST);
+        res.largest = sizes;
+
+        Ok(res)
+    }
+}
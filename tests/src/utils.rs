@@ -1,29 +1,43 @@
-use std::{io::Write, process::Stdio};
-
+use convert_case::{Case, Casing};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use sitter_ast::RuleJSON;
+use sitter_ast::{Context, GrammarJSON};
 
-fn format_string(input: String) -> String {
-    let mut cmd = std::process::Command::new("rustfmt");
-    cmd.stdin(Stdio::piped());
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::null());
-    cmd.arg("-q");
-    let mut v = cmd.spawn().unwrap();
-    v.stdin.as_mut().unwrap().write_all(input.as_bytes()).unwrap();
+/// Formats the type definitions of `input`, leaving out `impl` blocks and the preamble.
+fn format_types(input: TokenStream) -> String {
+    let mut file: syn::File = syn::parse2(input).unwrap();
+    file.items.retain(|item| match item {
+        syn::Item::Enum(item) => {
+            !["NodeKind", "AnyNode", "AnyNodeRef"].iter().any(|name| item.ident == name)
+        }
+        syn::Item::Struct(_) | syn::Item::Type(_) | syn::Item::Mod(_) => true,
+        _ => false,
+    });
 
-    let output = v.wait_with_output().unwrap();
-    String::from_utf8(output.stdout).unwrap()
+    prettyplease::unparse(&file)
 }
 
-pub fn test_ast(name: &str, source: &str, expected: TokenStream) -> bool {
-    let ast: RuleJSON = serde_json::from_str(source).unwrap();
-    let ident = syn::Ident::new(name, Span::call_site());
+#[allow(dead_code)]
+pub fn generate(ctx: &mut Context, name: &str, source: &str) -> TokenStream {
+    let ident = syn::Ident::new(&name.to_case(Case::UpperCamel), Span::call_site());
+
+    let name = name.to_case(Case::Snake);
+    let grammar = format!(r#"{{ "name": "test", "rules": {{ "{name}": {source} }} }}"#);
+    let grammar = serde_json::from_str::<GrammarJSON>(&grammar).unwrap().lower();
+    ctx.analyze(&grammar);
+    ctx.set_rule(&name);
 
-    let ast = ast.generate(&ident).unwrap();
-    let generated = format_string(ast.to_string());
-    let target = format_string(expected.to_string());
+    grammar.rules[&name].generate(&ident, ctx).unwrap()
+}
+
+#[allow(dead_code)]
+pub fn test_ast(name: &str, source: &str, expected: TokenStream) -> bool {
+    let ast = generate(&mut Context::new(), name, source);
+    let generated = format_types(ast);
+    let target = format_types(expected);
 
+    if generated != target {
+        eprintln!("generated:\n{generated}\nexpected:\n{target}");
+    }
     generated == target
 }
@@ -0,0 +1,154 @@
+include!("./src/utils.rs");
+
+#[test]
+fn test_alias() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "alias",
+      "rules": {
+        "declaration": {
+          "type": "SEQ",
+          "members": [
+            { "type": "ALIAS", "content": { "type": "STRING", "value": "let" }, "named": false, "value": "var" },
+            {
+              "type": "ALIAS",
+              "content": { "type": "SYMBOL", "name": "identifier" },
+              "named": true,
+              "value": "type_identifier"
+            },
+            { "type": "SYMBOL", "name": "identifier" }
+          ]
+        },
+        "type": {
+          "type": "CHOICE",
+          "members": [
+            {
+              "type": "ALIAS",
+              "content": { "type": "SYMBOL", "name": "identifier" },
+              "named": true,
+              "value": "type_identifier"
+            },
+            { "type": "ALIAS", "content": { "type": "SYMBOL", "name": "declaration" }, "named": true, "value": "declaration" }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated.clone()),
+        format_types(quote! {
+
+            #[derive(Debug)]
+            pub struct TypeIdentifier {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Declaration {
+                pub var: tokens::Var,
+                pub type_identifier: TypeIdentifier,
+                pub identifier: Identifier,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Identifier {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub enum Type {
+                TypeIdentifier(TypeIdentifier, Span),
+                Declaration(Declaration, Span),
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct Var;
+
+                impl TSParser for Var {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "var"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("var", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+        })
+    );
+
+    let generated = generated.to_string();
+    assert!(generated.contains(r#"node . kind () == "type_identifier""#));
+    assert!(generated.contains(r#"node . kind () == "declaration""#));
+}
+
+#[test]
+fn test_alias_field() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "alias",
+      "rules": {
+        "parameter": {
+          "type": "SEQ",
+          "members": [
+            {
+              "type": "FIELD",
+              "name": "type",
+              "content": {
+                "type": "ALIAS",
+                "content": { "type": "SYMBOL", "name": "identifier" },
+                "named": true,
+                "value": "type_identifier"
+              }
+            },
+            {
+              "type": "FIELD",
+              "name": "names",
+              "content": { "type": "REPEAT1", "content": { "type": "SYMBOL", "name": "identifier" } }
+            },
+            {
+              "type": "FIELD",
+              "name": "default",
+              "content": {
+                "type": "SEQ",
+                "members": [{ "type": "STRING", "value": "=" }, { "type": "SYMBOL", "name": "identifier" }]
+              }
+            }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    let types = format_types(generated.clone());
+    assert!(
+        types.contains(
+            "pub struct Parameter {
+    pub type_identifier: TypeIdentifier,
+    pub identifier: Vec<Identifier>,
+    pub parameter_token_2: ParameterToken2,
+    pub span: Span,
+}"
+        ),
+        "{types}"
+    );
+    assert!(generated.to_string().contains("TypeIdentifier (TypeIdentifier)"));
+}
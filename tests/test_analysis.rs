@@ -0,0 +1,93 @@
+use sitter_ast::{Analysis, GrammarJSON};
+
+const GRAMMAR: &str = r##"
+{
+  "name": "analysis",
+  "word": "identifier",
+  "externals": [
+    { "type": "SYMBOL", "name": "indent" },
+    { "type": "SYMBOL", "name": "dedent" },
+    { "type": "STRING", "value": "\n" },
+    { "type": "SYMBOL", "name": "__error_recovery" }
+  ],
+  "extras": [{ "type": "SYMBOL", "name": "comment" }],
+  "precedences": [[{ "type": "STRING", "value": "call" }, { "type": "SYMBOL", "name": "block" }]],
+  "conflicts": [["block", "statement"], ["block", "missing"]],
+  "rules": {
+    "module": { "type": "REPEAT", "content": { "type": "SYMBOL", "name": "statement" } },
+    "statement": {
+      "type": "CHOICE",
+      "members": [
+        { "type": "SYMBOL", "name": "block" },
+        { "type": "PREC", "value": "call", "content": { "type": "SYMBOL", "name": "call" } },
+        { "type": "PREC_LEFT", "value": "binary", "content": { "type": "SYMBOL", "name": "expression" } }
+      ]
+    },
+    "block": {
+      "type": "SEQ",
+      "members": [
+        { "type": "SYMBOL", "name": "indent" },
+        { "type": "SYMBOL", "name": "module" },
+        { "type": "SYMBOL", "name": "dedent" }
+      ]
+    },
+    "call": { "type": "SEQ", "members": [{ "type": "SYMBOL", "name": "identifier" }, { "type": "SYMBOL", "name": "arguments" }] },
+    "arguments": { "type": "PATTERN", "value": "\\(\\)" },
+    "unused": { "type": "SEQ", "members": [{ "type": "SYMBOL", "name": "module" }, { "type": "BLANK" }] },
+    "identifier": { "type": "PATTERN", "value": "[a-z]+" },
+    "comment": { "type": "PATTERN", "value": "#.*" }
+  }
+}
+"##;
+
+#[test]
+fn test_analysis() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+
+    let analysis = grammar.analyze();
+    assert_eq!(
+        analysis,
+        Analysis {
+            unreachable: vec!["unused".to_string()],
+            undefined: vec!["expression".to_string()],
+            unused_externals: vec![r#""\n""#.to_string()],
+            undeclared_precedences: vec!["binary".to_string()],
+            unknown_conflicts: vec!["missing".to_string()],
+            // The start rule `module` may match an empty document.
+            nullable: vec!["unused".to_string()],
+        }
+    );
+    assert_eq!(
+        analysis.to_string(),
+        r#"unreachable rules: unused
+undefined rules: expression
+unused externals: "\n"
+undeclared precedences: binary
+unknown rules in conflicts: missing
+rules matching the empty string: unused
+"#
+    );
+}
+
+#[test]
+fn test_analysis_empty() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"{ "name": "analysis", "rules": { "module": { "type": "STRING", "value": "x" } } }"#,
+    )
+    .unwrap();
+
+    let analysis = grammar.analyze();
+    assert!(analysis.is_empty());
+    assert_eq!(analysis.to_string(), "no problems found\n");
+    assert_eq!(
+        serde_json::to_value(&analysis).unwrap(),
+        serde_json::json!({
+            "unreachable": [],
+            "undefined": [],
+            "unused_externals": [],
+            "undeclared_precedences": [],
+            "unknown_conflicts": [],
+            "nullable": []
+        })
+    );
+}
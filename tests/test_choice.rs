@@ -2,7 +2,7 @@ include!("./src/utils.rs");
 
 #[test]
 fn test_choice() {
-    test_ast(
+    assert!(test_ast(
         "signed_short_int",
         r#"
     {
@@ -26,5 +26,40 @@ fn test_choice() {
                 Int16,
             }
         },
-    );
+    ));
+}
+
+#[test]
+fn test_choice_punctuation() {
+    assert!(test_ast(
+        "assignment_operator",
+        r##"
+    {
+      "type": "CHOICE",
+      "members": [
+        { "type": "STRING", "value": "=" },
+        { "type": "STRING", "value": "+=" },
+        { "type": "STRING", "value": "<<=" },
+        { "type": "STRING", "value": "->" },
+        { "type": "STRING", "value": "::" },
+        { "type": "STRING", "value": "#if" },
+        { "type": "STRING", "value": "if" },
+        { "type": "STRING", "value": "IF" }
+      ]
+    }
+    "##,
+        quote! {
+            #[derive(Debug)]
+            pub enum AssignmentOperator {
+                Eq,
+                PlusEq,
+                ShlEq,
+                Arrow,
+                ColonColon,
+                HashIf,
+                If,
+                If2,
+            }
+        },
+    ));
 }
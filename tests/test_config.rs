@@ -0,0 +1,103 @@
+include!("./src/utils.rs");
+
+use sitter_ast::Config;
+
+const GRAMMAR: &str = r#"
+{
+  "name": "idl",
+  "rules": {
+    "expression": {
+      "type": "CHOICE",
+      "members": [
+        { "type": "SYMBOL", "name": "scoped_name" },
+        { "type": "SYMBOL", "name": "comment" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "scoped_name": {
+      "type": "SEQ",
+      "members": [
+        { "type": "SYMBOL", "name": "identifier" },
+        { "type": "STRING", "value": "::" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "identifier": { "type": "PATTERN", "value": "[a-z]+" },
+    "comment": { "type": "PATTERN", "value": "//.*" }
+  }
+}
+"#;
+
+const CONFIG: &str = r#"
+[rules.scoped_name]
+rename = "QualifiedName"
+derives = ["serde::Serialize"]
+attributes = ["non_exhaustive"]
+fields = { identifier_2 = "name" }
+
+[rules.expression]
+variants = { Identifier = "Ident" }
+
+[rules.identifier]
+type = "crate::Identifier"
+
+[rules.comment]
+skip = true
+"#;
+
+#[test]
+fn test_config() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str(CONFIG).unwrap();
+
+    let generated = grammar.to_toke_stream_with(&config).unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+
+            #[derive(Debug)]
+            pub enum Expression {
+                QualifiedName(QualifiedName, Span),
+                Ident(Identifier, Span),
+            }
+
+            pub type Identifier = crate::Identifier;
+
+            #[derive(Debug, serde::Serialize)]
+            #[non_exhaustive]
+            pub struct QualifiedName {
+                pub identifier: Identifier,
+                pub colon_colon: tokens::ColonColon,
+                pub name: Identifier,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct ColonColon;
+
+                impl TSParser for ColonColon {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "::"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("::", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_config_unknown_rule() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str("[rules.missing]\nskip = true").unwrap();
+
+    let err = grammar.to_toke_stream_with(&config).unwrap_err();
+    assert_eq!(err.to_string(), "unknown rule `missing` in config");
+}
@@ -0,0 +1,107 @@
+include!("./src/utils.rs");
+
+use sitter_ast::Config;
+
+const GRAMMAR: &str = r#"
+{
+  "name": "idl",
+  "rules": {
+    "expression": {
+      "type": "CHOICE",
+      "members": [
+        { "type": "SYMBOL", "name": "scoped_name" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "scoped_name": {
+      "type": "SEQ",
+      "members": [
+        { "type": "SYMBOL", "name": "identifier" },
+        { "type": "STRING", "value": "::" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+  }
+}
+"#;
+
+fn generate_with(config: &str) -> anyhow::Result<TokenStream> {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str(config).unwrap();
+
+    grammar.to_toke_stream_with(&config)
+}
+
+#[test]
+fn test_derives() {
+    let generated =
+        generate_with(r#"derives = ["Clone", "PartialEq", "Eq", "Hash", "PartialOrd"]"#).unwrap();
+    let file: syn::File = syn::parse2(generated).unwrap();
+
+    let derives = quote!(#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd)]).to_string();
+    let mut items = file.items.clone();
+    while let Some(item) = items.pop() {
+        let mut attrs = match item {
+            syn::Item::Struct(item) => item.attrs,
+            // Like the `Keyword` enum, node kinds always derive the traits of a plain enum.
+            syn::Item::Enum(item) if item.ident == "NodeKind" => continue,
+            // References are copied whatever the types they refer to derive.
+            syn::Item::Enum(item) if item.ident == "AnyNodeRef" => continue,
+            syn::Item::Enum(item) => item.attrs,
+            syn::Item::Mod(item) => {
+                items.extend(item.content.unwrap().1);
+                continue;
+            }
+            _ => continue,
+        };
+        attrs.retain(|attr| !attr.path().is_ident("doc"));
+        assert_eq!(quote!(#(#attrs)*).to_string(), derives);
+    }
+}
+
+#[test]
+fn test_derives_of_rule() {
+    let generated = generate_with(
+        r#"
+[rules.scoped_name]
+derives = ["serde::Serialize"]
+"#,
+    )
+    .unwrap();
+    let file: syn::File = syn::parse2(generated).unwrap();
+
+    let serialized: Vec<_> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Struct(item) => Some((&item.ident, &item.attrs)),
+            syn::Item::Enum(item) => Some((&item.ident, &item.attrs)),
+            _ => None,
+        })
+        .filter(|(_, attrs)| quote!(#(#attrs)*).to_string().contains("Serialize"))
+        .map(|(ident, _)| ident.to_string())
+        .collect();
+    assert_eq!(serialized, ["ScopedName"]);
+}
+
+#[test]
+fn test_derives_unsatisfied() {
+    let err = generate_with(
+        r#"
+[rules.scoped_name]
+derives = ["Hash"]
+"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "type `ScopedName` derives `Hash` but its field type `Identifier` does not"
+    );
+
+    let err = generate_with(r#"derives = ["Eq"]"#).unwrap_err();
+    assert_eq!(err.to_string(), "type `AnyNode` derives `Eq` without `PartialEq`");
+
+    let err = generate_with(r#"derives = ["Default"]"#).unwrap_err();
+    assert_eq!(err.to_string(), "type `AnyNode` is an enum and can not derive `Default`");
+}
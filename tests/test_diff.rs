@@ -0,0 +1,160 @@
+use sitter_ast::{AstModel, ChangeKind, Config, GrammarJSON, Shape, TypeRef, VariantDef};
+
+fn model(rules: &str) -> AstModel {
+    let grammar = format!(r#"{{ "name": "diff", "rules": {rules} }}"#);
+    serde_json::from_str::<GrammarJSON>(&grammar).unwrap().to_model().unwrap()
+}
+
+#[test]
+fn test_diff() {
+    let old = model(
+        r#"
+    {
+      "call": {
+        "type": "SEQ",
+        "members": [
+          { "type": "SYMBOL", "name": "name" },
+          { "type": "SYMBOL", "name": "value" },
+          { "type": "SYMBOL", "name": "flag" }
+        ]
+      },
+      "name": { "type": "PATTERN", "value": "[a-z]+" },
+      "value": {
+        "type": "CHOICE",
+        "members": [{ "type": "SYMBOL", "name": "name" }, { "type": "STRING", "value": "null" }]
+      },
+      "flag": { "type": "STRING", "value": "!" }
+    }
+    "#,
+    );
+    let new = model(
+        r#"
+    {
+      "call": {
+        "type": "SEQ",
+        "members": [
+          { "type": "SYMBOL", "name": "name" },
+          { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "value" }, { "type": "BLANK" }] },
+          { "type": "SYMBOL", "name": "number" }
+        ]
+      },
+      "name": { "type": "PATTERN", "value": "[a-z]+" },
+      "value": {
+        "type": "CHOICE",
+        "members": [{ "type": "SYMBOL", "name": "number" }, { "type": "STRING", "value": "null" }]
+      },
+      "number": { "type": "PATTERN", "value": "[0-9]+" }
+    }
+    "#,
+    );
+
+    let diff = old.diff(&new);
+    assert!(diff.is_breaking());
+    assert_eq!(
+        diff.to_string(),
+        "\
+breaking: field `Call::value` changed from one to optional
+breaking: field `Call::flag` removed
+breaking: field `Call::number` added
+breaking: type `Flag` removed
+breaking: variant `Value::Name` removed
+breaking: variant `Value::Number` added
+non-breaking: type `Number` added
+"
+    );
+
+    assert!(old.diff(&old).is_empty());
+    assert_eq!(old.diff(&old).to_string(), "no changes\n");
+}
+
+#[test]
+fn test_diff_literals() {
+    let grammar = |literals: &str| {
+        let grammar = format!(
+            r#"
+    {{
+      "name": "diff",
+      "word": "identifier",
+      "rules": {{
+        "statement": {{
+          "type": "SEQ",
+          "members": [
+            {{ "type": "CHOICE", "members": [{literals}] }},
+            {{ "type": "SYMBOL", "name": "identifier" }}
+          ]
+        }},
+        "identifier": {{ "type": "PATTERN", "value": "[a-z]+" }}
+      }}
+    }}
+    "#
+        );
+        serde_json::from_str::<GrammarJSON>(&grammar).unwrap().to_model().unwrap()
+    };
+    let old =
+        grammar(r#"{ "type": "STRING", "value": "return" }, { "type": "STRING", "value": "!" }"#);
+    let new = grammar(
+        r#"{ "type": "STRING", "value": "return" }, { "type": "STRING", "value": "yield" }"#,
+    );
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.to_string(),
+        "\
+breaking: variant `StatementToken0::Bang` removed
+breaking: variant `StatementToken0::Yield` added
+breaking: token `tokens::Bang` removed
+non-breaking: token `tokens::Yield` added
+breaking: keyword `yield` added
+"
+    );
+    assert_eq!(
+        new.diff(&old).changes.last().map(|change| &change.kind),
+        Some(&ChangeKind::KeywordRemoved { value: "yield".to_string() })
+    );
+}
+
+#[test]
+fn test_diff_renamed() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "diff",
+      "rules": {
+        "document": { "type": "SYMBOL", "name": "value" },
+        "value": {
+          "type": "CHOICE",
+          "members": [{ "type": "PATTERN", "value": "[a-z]+" }, { "type": "STRING", "value": "null" }]
+        }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+    let old = grammar.to_model().unwrap();
+    let config: Config =
+        toml::from_str("[rules.value]\nrename = \"Item\"\nattributes = [\"non_exhaustive\"]")
+            .unwrap();
+    let new = grammar.to_model_with(&config).unwrap();
+
+    let diff = old.diff(&new);
+    let changes: Vec<_> =
+        diff.changes.iter().map(|change| (change.breaking, &change.kind)).collect();
+    assert_eq!(
+        changes,
+        [(true, &ChangeKind::TypeRenamed { from: "Value".to_string(), to: "Item".to_string() })]
+    );
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert_eq!(
+        json["changes"][0],
+        serde_json::json!({ "breaking": true, "change": "type_renamed", "from": "Value", "to": "Item" })
+    );
+
+    let mut next = new.clone();
+    let Shape::Enum { variants } = &mut next.types.last_mut().unwrap().shape else {
+        panic!("not an enum: {:?}", next.types.last());
+    };
+    let members = vec![TypeRef::Token { name: "True".to_string() }];
+    variants.push(VariantDef { name: "True".to_string(), members });
+    assert_eq!(new.diff(&next).to_string(), "non-breaking: variant `Item::True` added\n");
+}
@@ -0,0 +1,89 @@
+include!("./src/utils.rs");
+
+#[test]
+fn test_externals() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "externals",
+      "externals": [
+        { "type": "SYMBOL", "name": "heredoc_body" },
+        { "type": "SYMBOL", "name": "string_content" },
+        { "type": "STRING", "value": "||" }
+      ],
+      "rules": {
+        "heredoc": {
+          "type": "SEQ",
+          "members": [
+            { "type": "STRING", "value": "<<" },
+            { "type": "SYMBOL", "name": "heredoc_body" },
+            { "type": "SYMBOL", "name": "string_content" }
+          ]
+        },
+        "string_content": { "type": "PATTERN", "value": "[^\"]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+
+
+            #[derive(Debug)]
+            pub struct Heredoc {
+                pub shl: tokens::Shl,
+                pub heredoc_body: HeredocBody,
+                pub string_content: StringContent,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct StringContent {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct Shl;
+
+                impl TSParser for Shl {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "<<"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("<<", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+
+                #[derive(Debug)]
+                pub struct OrOr;
+
+                impl TSParser for OrOr {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "||"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("||", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+
+            #[derive(Debug)]
+            pub struct HeredocBody {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+        })
+    );
+}
@@ -0,0 +1,107 @@
+use sitter_ast::{GrammarJSON, Rule};
+
+fn lower(rules: &str, inline: &str) -> Vec<(String, Rule)> {
+    let grammar = format!(r#"{{ "name": "ir", "inline": [{inline}], "rules": {rules} }}"#);
+    let grammar: GrammarJSON = serde_json::from_str(&grammar).unwrap();
+    grammar.lower().rules.into_iter().collect()
+}
+
+fn symbol(name: &str) -> Rule {
+    Rule::Symbol { name: name.to_string() }
+}
+
+fn string(value: &str) -> Rule {
+    Rule::String { value: value.to_string() }
+}
+
+#[test]
+fn test_ir() {
+    let rules = lower(
+        r#"
+    {
+      "call": {
+        "type": "PREC_LEFT",
+        "value": 1,
+        "content": {
+          "type": "SEQ",
+          "members": [
+            { "type": "SYMBOL", "name": "name" },
+            { "type": "SEQ", "members": [{ "type": "STRING", "value": "(" }, { "type": "BLANK" }] },
+            {
+              "type": "CHOICE",
+              "members": [
+                { "type": "REPEAT1", "content": { "type": "SYMBOL", "name": "name" } },
+                { "type": "BLANK" }
+              ]
+            },
+            { "type": "STRING", "value": ")" }
+          ]
+        }
+      },
+      "name": {
+        "type": "CHOICE",
+        "members": [
+          { "type": "SYMBOL", "name": "_identifier" },
+          { "type": "CHOICE", "members": [{ "type": "PREC", "value": "name", "content": { "type": "SYMBOL", "name": "number" } }] },
+          { "type": "SYMBOL", "name": "number" }
+        ]
+      },
+      "_identifier": { "type": "PATTERN", "value": "[a-z]+" },
+      "number": {
+        "type": "REPEAT1",
+        "content": { "type": "CHOICE", "members": [{ "type": "BLANK" }, { "type": "PATTERN", "value": "\\d" }] }
+      }
+    }
+    "#,
+        r#""_identifier""#,
+    );
+
+    let pattern = |value: &str| Rule::Pattern { value: value.to_string(), flags: None };
+    assert_eq!(
+        rules,
+        vec![
+            (
+                "call".to_string(),
+                Rule::Seq {
+                    members: vec![
+                        symbol("name"),
+                        string("("),
+                        Rule::Repeat { content: Box::new(symbol("name")) },
+                        string(")"),
+                    ]
+                }
+            ),
+            (
+                "name".to_string(),
+                Rule::Choice { members: vec![pattern("[a-z]+"), symbol("number")] }
+            ),
+            ("number".to_string(), Rule::Repeat { content: Box::new(pattern("\\d")) }),
+        ]
+    );
+}
+
+#[test]
+fn test_ir_recursive_inline() {
+    let rules = lower(
+        r#"
+    {
+      "list": { "type": "SYMBOL", "name": "_items" },
+      "_items": {
+        "type": "SEQ",
+        "members": [{ "type": "STRING", "value": "x" }, { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "_items" }, { "type": "BLANK" }] }]
+      }
+    }
+    "#,
+        r#""_items""#,
+    );
+
+    assert_eq!(
+        rules,
+        vec![(
+            "list".to_string(),
+            Rule::Seq {
+                members: vec![string("x"), Rule::Optional { content: Box::new(symbol("_items")) }]
+            }
+        )]
+    );
+}
@@ -0,0 +1,99 @@
+include!("./src/utils.rs");
+
+/// Formats the `Keyword` enum of `input` with its `impl` blocks.
+fn format_keyword(input: TokenStream) -> String {
+    let mut file: syn::File = syn::parse2(input).unwrap();
+    file.items.retain(|item| match item {
+        syn::Item::Enum(item) => item.ident == "Keyword",
+        syn::Item::Impl(item) => {
+            let self_ty = &item.self_ty;
+            quote!(#self_ty).to_string() == "Keyword"
+        }
+        _ => false,
+    });
+
+    prettyplease::unparse(&file)
+}
+
+#[test]
+fn test_keywords() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "keywords",
+      "word": "identifier",
+      "rules": {
+        "statement": {
+          "type": "SEQ",
+          "members": [
+            { "type": "STRING", "value": "if" },
+            { "type": "SYMBOL", "name": "identifier" },
+            { "type": "STRING", "value": "{" },
+            { "type": "STRING", "value": "}" },
+            {
+              "type": "CHOICE",
+              "members": [
+                { "type": "SEQ", "members": [{ "type": "STRING", "value": "else" }, { "type": "SYMBOL", "name": "statement" }] },
+                { "type": "BLANK" }
+              ]
+            },
+            { "type": "TOKEN", "content": { "type": "STRING", "value": "end" } }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z_]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_keyword(generated),
+        format_keyword(quote! {
+            /// Keywords of the grammar, the literal strings matched by its word rule.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub enum Keyword {
+                Else,
+                End,
+                If,
+            }
+
+            impl Keyword {
+                pub const ALL: &'static [Keyword] = &[Keyword::Else, Keyword::End, Keyword::If];
+
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        Keyword::Else => "else",
+                    Keyword::End => "end",
+                        Keyword::If => "if",
+                    }
+                }
+            }
+
+            impl std::str::FromStr for Keyword {
+                type Err = ParseError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    match value {
+                        "else" => Ok(Keyword::Else),
+                "end" => Ok(Keyword::End),
+                        "if" => Ok(Keyword::If),
+                        _ => Err(ParseError::UnknownKeyword(value.to_string())),
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_keywords_without_word() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"{ "name": "keywords", "rules": { "statement": { "type": "STRING", "value": "if" } } }"#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(format_keyword(generated), "");
+}
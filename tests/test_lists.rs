@@ -0,0 +1,105 @@
+include!("./src/utils.rs");
+
+#[test]
+fn test_separated_list() {
+    assert!(test_ast(
+        "arguments",
+        r#"
+    {
+      "type": "SEQ",
+      "members": [
+        { "type": "STRING", "value": "(" },
+        {
+          "type": "CHOICE",
+          "members": [
+            {
+              "type": "SEQ",
+              "members": [
+                { "type": "SYMBOL", "name": "expression" },
+                {
+                  "type": "REPEAT",
+                  "content": {
+                    "type": "SEQ",
+                    "members": [{ "type": "STRING", "value": "," }, { "type": "SYMBOL", "name": "expression" }]
+                  }
+                }
+              ]
+            },
+            { "type": "BLANK" }
+          ]
+        },
+        { "type": "STRING", "value": ")" }
+      ]
+    }
+    "#,
+        quote! {
+            #[derive(Debug)]
+            pub struct Arguments {
+                pub l_paren: tokens::LParen,
+                pub expression: Vec<Expression>,
+                pub r_paren: tokens::RParen,
+            }
+        },
+    ));
+}
+
+#[test]
+fn test_separated_list_inline() {
+    assert!(test_ast(
+        "array",
+        r#"
+    {
+      "type": "SEQ",
+      "members": [
+        { "type": "STRING", "value": "[" },
+        { "type": "SYMBOL", "name": "number" },
+        {
+          "type": "REPEAT",
+          "content": {
+            "type": "SEQ",
+            "members": [{ "type": "STRING", "value": ";" }, { "type": "SYMBOL", "name": "number" }]
+          }
+        },
+        { "type": "CHOICE", "members": [{ "type": "STRING", "value": ";" }, { "type": "BLANK" }] },
+        { "type": "STRING", "value": "]" }
+      ]
+    }
+    "#,
+        quote! {
+            #[derive(Debug)]
+            pub struct Array {
+                pub l_bracket: tokens::LBracket,
+                pub number: Vec<Number>,
+                pub r_bracket: tokens::RBracket,
+            }
+        },
+    ));
+}
+
+#[test]
+fn test_separated_list_rule() {
+    assert!(test_ast(
+        "names",
+        r#"
+    {
+      "type": "SEQ",
+      "members": [
+        { "type": "SYMBOL", "name": "identifier" },
+        {
+          "type": "REPEAT",
+          "content": {
+            "type": "SEQ",
+            "members": [{ "type": "STRING", "value": "," }, { "type": "SYMBOL", "name": "identifier" }]
+          }
+        }
+      ]
+    }
+    "#,
+        quote! {
+            #[derive(Debug)]
+            pub struct Names {
+                pub value: Vec<Identifier>
+            }
+        },
+    ));
+}
@@ -0,0 +1,132 @@
+use sitter_ast::{GrammarJSON, Shape};
+
+fn grammar(rules: &str) -> GrammarJSON {
+    let grammar = format!(r#"{{ "name": "model", "rules": {rules} }}"#);
+    serde_json::from_str(&grammar).unwrap()
+}
+
+#[test]
+fn test_model() {
+    let grammar = grammar(
+        r#"
+    {
+      "call": {
+        "type": "SEQ",
+        "members": [
+          { "type": "SYMBOL", "name": "name" },
+          { "type": "STRING", "value": "(" },
+          { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "name" }, { "type": "BLANK" }] },
+          { "type": "REPEAT", "content": { "type": "SYMBOL", "name": "value" } },
+          { "type": "STRING", "value": ")" }
+        ]
+      },
+      "name": { "type": "PATTERN", "value": "[a-z]+" },
+      "value": {
+        "type": "CHOICE",
+        "members": [{ "type": "SYMBOL", "name": "name" }, { "type": "STRING", "value": "null" }]
+      }
+    }
+    "#,
+    );
+
+    let model = grammar.to_model().unwrap();
+    let expected = serde_json::json!({
+        "name": "model",
+        "root": "Call",
+        "types": [
+            {
+                "name": "Call",
+                "rule": "call",
+                "module": "call",
+                "kind": "call",
+                "derives": ["Debug"],
+                "attributes": [],
+                "shape": "struct",
+                "fields": [
+                    { "name": "name", "type": { "kind": "type", "name": "Name", "boxed": false }, "cardinality": "one" },
+                    { "name": "l_paren", "type": { "kind": "token", "name": "LParen" }, "cardinality": "one" },
+                    { "name": "name_2", "type": { "kind": "type", "name": "Name", "boxed": false }, "cardinality": "optional" },
+                    { "name": "value", "type": { "kind": "type", "name": "Value", "boxed": false }, "cardinality": "many" },
+                    { "name": "r_paren", "type": { "kind": "token", "name": "RParen" }, "cardinality": "one" }
+                ]
+            },
+            {
+                "name": "Name",
+                "rule": "name",
+                "module": "name",
+                "kind": "name",
+                "derives": ["Debug"],
+                "attributes": [],
+                "shape": "leaf"
+            },
+            {
+                "name": "Value",
+                "rule": "value",
+                "module": "value",
+                "kind": "value",
+                "derives": ["Debug"],
+                "attributes": [],
+                "shape": "enum",
+                "variants": [
+                    { "name": "Name", "members": [{ "kind": "type", "name": "Name", "boxed": false }] },
+                    { "name": "Null", "members": [{ "kind": "token", "name": "Null" }] }
+                ]
+            }
+        ],
+        "tokens": [
+            { "name": "LParen", "value": "(" },
+            { "name": "RParen", "value": ")" },
+            { "name": "Null", "value": "null" }
+        ],
+        "keywords": [],
+        "kinds": ["call", "name", "value"]
+    });
+    assert_eq!(serde_json::to_value(&model).unwrap(), expected);
+
+    let source = grammar.to_source_with(&Default::default()).unwrap();
+    assert!(source.contains("pub name_2: Option<Name>,"), "{source}");
+    assert!(source.contains("pub value: Vec<Value>,"), "{source}");
+}
+
+#[test]
+fn test_model_unique_names() {
+    let grammar = grammar(
+        r#"
+    {
+      "document": {
+        "type": "CHOICE",
+        "members": [
+          { "type": "ALIAS", "content": { "type": "STRING", "value": "a" }, "named": true, "value": "value" },
+          { "type": "ALIAS", "content": { "type": "STRING", "value": "b" }, "named": true, "value": "value" },
+          { "type": "SYMBOL", "name": "_content" },
+          { "type": "SYMBOL", "name": "content" }
+        ]
+      },
+      "_content": {
+        "type": "SEQ",
+        "members": [{ "type": "STRING", "value": "-" }, { "type": "SYMBOL", "name": "content" }]
+      },
+      "content": { "type": "PATTERN", "value": "[0-9]+" }
+    }
+    "#,
+    );
+
+    let model = grammar.to_model().unwrap();
+    let names: Vec<_> =
+        model.types.iter().map(|def| (def.rule.as_str(), def.name.as_str())).collect();
+    assert_eq!(
+        names,
+        [
+            ("_content", "Content2"),
+            ("content", "Content"),
+            ("document", "Value"),
+            ("document", "Document")
+        ]
+    );
+
+    let Shape::Enum { variants } = &model.types[3].shape else {
+        panic!("not an enum: {:?}", model.types[3]);
+    };
+    let variants: Vec<_> = variants.iter().map(|variant| variant.name.as_str()).collect();
+    assert_eq!(variants, ["Value", "Content2", "Content"]);
+}
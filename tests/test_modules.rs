@@ -0,0 +1,61 @@
+use sitter_ast::{Config, GrammarJSON};
+
+const GRAMMAR: &str = r#"
+{
+  "name": "idl",
+  "rules": {
+    "definition": { "type": "SYMBOL", "name": "scoped_name" },
+    "scoped_name": {
+      "type": "SEQ",
+      "members": [
+        { "type": "STRING", "value": "::" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+  }
+}
+"#;
+
+#[test]
+fn test_modules() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config =
+        toml::from_str("[groups]\nnames = [\"scoped_name\", \"identifier\"]").unwrap();
+
+    let sources = grammar.to_module_sources_with(&config).unwrap();
+    let names: Vec<_> = sources.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["mod.rs", "definition.rs", "names.rs", "tokens.rs"]);
+
+    let source = |name: &str| &sources.iter().find(|(item, _)| item == name).unwrap().1;
+    assert!(source("mod.rs").contains("use sitter_ast_runtime::{"));
+    assert!(source("mod.rs").contains("mod names;\npub use names::*;\n"));
+    assert!(!source("mod.rs").contains("externals"));
+    assert!(source("names.rs").contains("pub struct Identifier {"));
+    assert!(source("names.rs").contains("pub struct ScopedName {"));
+    assert!(source("tokens.rs").contains("pub struct ColonColon;"));
+    assert!(source("definition.rs").contains(
+        "pub struct Definition {\n    pub scoped_name: ScopedName,\n    pub span: Span,\n}"
+    ));
+}
+
+#[test]
+fn test_modules_externals() {
+    let mut grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    grammar.externals =
+        serde_json::from_str(r#"[{ "type": "SYMBOL", "name": "heredoc" }]"#).unwrap();
+
+    let sources = grammar.to_module_sources_with(&Config::default()).unwrap();
+    let source = |name: &str| &sources.iter().find(|(item, _)| item == name).unwrap().1;
+    assert!(source("mod.rs").contains("mod externals;\npub use externals::*;\n"));
+    assert!(source("externals.rs").contains("pub struct Heredoc {"));
+}
+
+#[test]
+fn test_modules_bad_group() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str("[groups]\ntokens = [\"identifier\"]").unwrap();
+
+    let err = grammar.to_module_sources_with(&config).unwrap_err();
+    assert_eq!(err.to_string(), "bad group name `tokens`");
+}
@@ -0,0 +1,318 @@
+include!("./src/utils.rs");
+
+const GRAMMAR: &str = r#"
+    {
+      "name": "nodes",
+      "externals": [{ "type": "SYMBOL", "name": "heredoc" }, { "type": "SYMBOL", "name": "_semi" }],
+      "rules": {
+        "program": { "type": "REPEAT", "content": { "type": "SYMBOL", "name": "_statement" } },
+        "_statement": {
+          "type": "CHOICE",
+          "members": [
+            { "type": "SYMBOL", "name": "expression_statement" },
+            { "type": "ALIAS", "content": { "type": "SYMBOL", "name": "_declaration" }, "named": true, "value": "let_declaration" }
+          ]
+        },
+        "expression_statement": {
+          "type": "SEQ",
+          "members": [
+            { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "identifier" }, { "type": "SYMBOL", "name": "heredoc" }] },
+            { "type": "STRING", "value": ";" }
+          ]
+        },
+        "_declaration": {
+          "type": "SEQ",
+          "members": [
+            { "type": "ALIAS", "content": { "type": "STRING", "value": "let" }, "named": false, "value": "var" },
+            { "type": "ALIAS", "content": { "type": "SYMBOL", "name": "identifier" }, "named": true, "value": "variable" }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+"#;
+
+/// Formats the item named `name` in `input` with its `impl` blocks.
+fn format_item(input: TokenStream, name: &str) -> String {
+    let mut file: syn::File = syn::parse2(input).unwrap();
+    file.items.retain(|item| match item {
+        syn::Item::Enum(item) => item.ident == name,
+        syn::Item::Impl(item) => {
+            let self_ty = &item.self_ty;
+            item.trait_.is_none() && quote!(#self_ty).to_string() == name
+        }
+        _ => false,
+    });
+
+    prettyplease::unparse(&file)
+}
+
+#[test]
+fn test_node_kinds() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let model = grammar.to_model().unwrap();
+    assert_eq!(
+        model.kinds,
+        ["program", "expression_statement", "identifier", "heredoc", "let_declaration", "variable"]
+    );
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_item(generated, "NodeKind"),
+        format_item(
+            quote! {
+                /// Named node kinds of the grammar, from its rules and aliases.
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                pub enum NodeKind {
+                    Program,
+                    ExpressionStatement,
+                    Identifier,
+                    Heredoc,
+                    LetDeclaration,
+                    Variable,
+                }
+
+                impl NodeKind {
+                    pub const ALL: &'static [NodeKind] = &[
+                        NodeKind::Program,
+                        NodeKind::ExpressionStatement,
+                        NodeKind::Identifier,
+                        NodeKind::Heredoc,
+                        NodeKind::LetDeclaration,
+                        NodeKind::Variable,
+                    ];
+
+                    pub fn as_str(&self) -> &'static str {
+                        match self {
+                            NodeKind::Program => "program",
+                            NodeKind::ExpressionStatement => "expression_statement",
+                            NodeKind::Identifier => "identifier",
+                            NodeKind::Heredoc => "heredoc",
+                            NodeKind::LetDeclaration => "let_declaration",
+                            NodeKind::Variable => "variable",
+                        }
+                    }
+
+                    /// The kind named `name`, none if the grammar has no such named node.
+                    pub fn from_name(name: &str) -> Option<Self> {
+                        match name {
+                            "program" => Some(NodeKind::Program),
+                            "expression_statement" => Some(NodeKind::ExpressionStatement),
+                            "identifier" => Some(NodeKind::Identifier),
+                            "heredoc" => Some(NodeKind::Heredoc),
+                            "let_declaration" => Some(NodeKind::LetDeclaration),
+                            "variable" => Some(NodeKind::Variable),
+                            _ => None,
+                        }
+                    }
+
+                    /// Kind of `node`, none for unnamed nodes.
+                    pub fn of(node: TSNode<'_>) -> Option<Self> {
+                        match node.is_named() {
+                            true => Self::from_name(node.kind()),
+                            false => None,
+                        }
+                    }
+                }
+            },
+            "NodeKind"
+        )
+    );
+}
+
+#[test]
+fn test_any_node() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_item(generated, "AnyNode"),
+        format_item(
+            quote! {
+                /// A value of any type generated for the grammar.
+                #[derive(Debug)]
+                pub enum AnyNode {
+                    Variable(Variable),
+                    Declaration(Declaration),
+                    LetDeclaration(LetDeclaration),
+                    Statement(Statement),
+                    ExpressionStatementToken0(ExpressionStatementToken0),
+                    ExpressionStatement(ExpressionStatement),
+                    Identifier(Identifier),
+                    Program(Program),
+                    Heredoc(Heredoc),
+                    Semi(Semi),
+                }
+
+                impl AnyNode {
+                    /// Converts `node` into the type generated for its kind.
+                    pub fn from_ts_node(node: TSNode<'_>, source: &[u8]) -> ParseResult<Self> {
+                        match NodeKind::of(node) {
+                            Some(NodeKind::Program) => Program::parse(node, source).map(AnyNode::Program),
+                            Some(NodeKind::ExpressionStatement) => {
+                                ExpressionStatement::parse(node, source).map(AnyNode::ExpressionStatement)
+                            }
+                            Some(NodeKind::Identifier) => Identifier::parse(node, source).map(AnyNode::Identifier),
+                            Some(NodeKind::Heredoc) => Heredoc::parse(node, source).map(AnyNode::Heredoc),
+                            Some(NodeKind::LetDeclaration) => {
+                                LetDeclaration::parse(node, source).map(AnyNode::LetDeclaration)
+                            }
+                            Some(NodeKind::Variable) => Variable::parse(node, source).map(AnyNode::Variable),
+                            _ => Err(ParseError::unexpected(node)),
+                        }
+                    }
+
+                    pub fn as_node_ref(&self) -> AnyNodeRef<'_> {
+                        match self {
+                            AnyNode::Variable(item) => AnyNodeRef::Variable(item),
+                            AnyNode::Declaration(item) => AnyNodeRef::Declaration(item),
+                            AnyNode::LetDeclaration(item) => AnyNodeRef::LetDeclaration(item),
+                            AnyNode::Statement(item) => AnyNodeRef::Statement(item),
+                            AnyNode::ExpressionStatementToken0(item) => {
+                                AnyNodeRef::ExpressionStatementToken0(item)
+                            }
+                            AnyNode::ExpressionStatement(item) => AnyNodeRef::ExpressionStatement(item),
+                            AnyNode::Identifier(item) => AnyNodeRef::Identifier(item),
+                            AnyNode::Program(item) => AnyNodeRef::Program(item),
+                            AnyNode::Heredoc(item) => AnyNodeRef::Heredoc(item),
+                            AnyNode::Semi(item) => AnyNodeRef::Semi(item),
+                        }
+                    }
+                }
+            },
+            "AnyNode"
+        )
+    );
+}
+
+#[test]
+fn test_node_enums_clash() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"{ "name": "clash", "rules": { "node_kind": { "type": "PATTERN", "value": "[a-z]+" } } }"#,
+    )
+    .unwrap();
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(format_item(generated.clone(), "AnyNode"), "");
+    assert!(!generated.to_string().contains("enum NodeKind"));
+}
+
+/// `AstNode` implementations of the types `names` in `input`.
+fn format_ast_nodes(input: TokenStream, names: &[&str]) -> String {
+    let mut file: syn::File = syn::parse2(input).unwrap();
+    file.items.retain(|item| {
+        let syn::Item::Impl(item) = item else {
+            return false;
+        };
+        let ty = &item.self_ty;
+        let path = item.trait_.as_ref().map(|(_, path, _)| quote!(#path).to_string());
+        names.contains(&quote!(#ty).to_string().as_str()) && path.as_deref() == Some("AstNode")
+    });
+
+    prettyplease::unparse(&file)
+}
+
+#[test]
+fn test_ast_node() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_ast_nodes(
+            generated,
+            &["ExpressionStatementToken0", "LetDeclaration", "Identifier", "Program", "Semi"]
+        ),
+        prettyplease::unparse(&syn::parse_quote! {
+            impl AstNode for LetDeclaration {
+                fn kind(&self) -> Option<NodeKind> {
+                    Some(NodeKind::LetDeclaration)
+                }
+
+                fn span(&self) -> Option<Span> {
+                    Some(self.span)
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    vec![AnyNodeRef::Variable(&self.variable)].into_iter()
+                }
+            }
+
+            impl AstNode for ExpressionStatementToken0 {
+                fn kind(&self) -> Option<NodeKind> {
+                    None
+                }
+
+                fn span(&self) -> Option<Span> {
+                    self.children().filter_map(|child| child.span()).reduce(Span::join)
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    let mut res: Vec<AnyNodeRef<'_>> = vec![];
+                    match self {
+                        Self::Identifier(item0) => {
+                            res.push(AnyNodeRef::Identifier(item0));
+                        }
+                        Self::Heredoc(item0) => {
+                            res.push(AnyNodeRef::Heredoc(item0));
+                        }
+                    }
+                    res.into_iter()
+                }
+            }
+
+            impl AstNode for Identifier {
+                fn kind(&self) -> Option<NodeKind> {
+                    Some(NodeKind::Identifier)
+                }
+
+                fn span(&self) -> Option<Span> {
+                    Some(self.span)
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    std::iter::empty()
+                }
+            }
+
+            impl AstNode for Program {
+                fn kind(&self) -> Option<NodeKind> {
+                    Some(NodeKind::Program)
+                }
+
+                fn span(&self) -> Option<Span> {
+                    Some(self.span)
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    let mut res: Vec<AnyNodeRef<'_>> = vec![];
+                    for item in &self.value {
+                        res.push(AnyNodeRef::Statement(item));
+                    }
+                    res.into_iter()
+                }
+            }
+
+            impl AstNode for Semi {
+                fn kind(&self) -> Option<NodeKind> {
+                    None
+                }
+
+                fn span(&self) -> Option<Span> {
+                    Some(self.span)
+                }
+
+                fn children(&self) -> impl Iterator<Item = AnyNodeRef<'_>> {
+                    std::iter::empty()
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_ast_node_clash() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"{ "name": "clash", "rules": { "ast_node": { "type": "PATTERN", "value": "[a-z]+" } } }"#,
+    )
+    .unwrap();
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(format_ast_nodes(generated.clone(), &["AstNode"]), "");
+    assert!(!generated.to_string().contains("enum AnyNodeRef"));
+}
@@ -0,0 +1,145 @@
+use sitter_ast::{Config, GrammarJSON};
+
+const GRAMMAR: &str = r#"
+{
+  "name": "idl",
+  "rules": {
+    "definition": { "type": "SYMBOL", "name": "scoped_name" },
+    "scoped_name": {
+      "type": "SEQ",
+      "members": [
+        { "type": "STRING", "value": "::" },
+        { "type": "SYMBOL", "name": "identifier" }
+      ]
+    },
+    "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+  }
+}
+"#;
+
+#[test]
+fn test_parse_str() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str("language = \"tree_sitter_idl::language\"").unwrap();
+
+    let source = grammar.to_source_with(&config).unwrap();
+    assert!(source.contains(
+        "impl Definition {
+    /// Parses `source` as a whole `idl` document.
+    pub fn parse_str(source: &str) -> ParseResult<Self> {
+        sitter_ast_runtime::parse_str(&tree_sitter_idl::language(), source)
+    }
+    /// Converts the `tree` parsed from `source`, failing on its first syntax error.
+    pub fn parse_tree("
+    ));
+    assert_eq!(grammar.module_name(), "idl");
+
+    let source = grammar.to_source_with(&Config::default()).unwrap();
+    assert!(!source.contains("fn parse_str"));
+    assert!(source.contains("impl Definition {\n    /// Converts the `tree`"));
+}
+
+#[test]
+fn test_parse_str_bad_language() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str("language = \"tree-sitter-idl\"").unwrap();
+
+    let err = grammar.to_source_with(&config).unwrap_err();
+    assert!(err.to_string().starts_with("bad language `tree-sitter-idl`"), "{err}");
+}
+
+#[test]
+fn test_kind_ids() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+    let config: Config = toml::from_str("language = \"tree_sitter_idl::language\"").unwrap();
+
+    let source = grammar.to_source_with(&config).unwrap();
+    assert!(
+        source.contains(
+            "fn kind_ids() -> &'static [u16; 4] {
+    static KIND_IDS: std::sync::OnceLock<[u16; 4]> = std::sync::OnceLock::new();
+    KIND_IDS
+        .get_or_init(|| {
+            let language = tree_sitter_idl::language();
+            [
+                language.id_for_node_kind(\"definition\", true),
+                language.id_for_node_kind(\"identifier\", true),
+                language.id_for_node_kind(\"scoped_name\", true),
+                language.id_for_node_kind(\"::\", false),
+            ]
+        })
+}"
+        ),
+        "{source}"
+    );
+    assert!(source.contains("node.kind_id() == kind_ids()[2]"));
+    assert!(!source.contains("node.kind() =="));
+
+    let source = grammar.to_source_with(&Config::default()).unwrap();
+    assert!(!source.contains("kind_ids"));
+    assert!(source.contains("node.kind() == \"scoped_name\""));
+}
+
+#[test]
+fn test_kind_ids_nested_token() {
+    let grammar = GRAMMAR.replace(
+        r#"{ "type": "PATTERN", "value": "[a-z]+" }"#,
+        r#"{ "type": "REPEAT1", "content": { "type": "PATTERN", "value": "[a-z]" } }"#,
+    );
+    let grammar: GrammarJSON = serde_json::from_str(&grammar).unwrap();
+    let config: Config = toml::from_str("language = \"tree_sitter_idl::language\"").unwrap();
+
+    // The nested pattern has no node of its own, only the one of `identifier` is checked.
+    let source = grammar.to_source_with(&config).unwrap();
+    assert!(source.contains("fn kind_ids() -> &'static [u16; 4] {"), "{source}");
+    assert!(!source.contains("IdentifierToken"), "{source}");
+}
+
+#[test]
+fn test_single_symbol_rule() {
+    let grammar: GrammarJSON = serde_json::from_str(GRAMMAR).unwrap();
+
+    let source = grammar.to_source_with(&Config::default()).unwrap();
+    assert!(source.contains(
+        "pub struct Definition {\n    pub scoped_name: ScopedName,\n    pub span: Span,\n}"
+    ));
+    assert!(source.contains(
+        "impl TSParser for Definition {
+    fn starts_with(node: TSNode<'_>) -> bool {
+        node.kind() == \"definition\"
+    }"
+    ));
+    assert!(source.contains(
+        "Some(NodeKind::Definition) => {
+                Definition::parse(node, source).map(AnyNode::Definition)
+            }"
+    ));
+}
+
+#[test]
+fn test_field_rule() {
+    let grammar = GRAMMAR.replace(
+        r#"{ "type": "SYMBOL", "name": "scoped_name" }"#,
+        r#"{ "type": "FIELD", "name": "name", "content": { "type": "SYMBOL", "name": "scoped_name" } }"#,
+    );
+    let grammar: GrammarJSON = serde_json::from_str(&grammar).unwrap();
+
+    let source = grammar.to_source_with(&Config::default()).unwrap();
+    assert!(source.contains(
+        "pub struct Definition {\n    pub scoped_name: ScopedName,\n    pub span: Span,\n}"
+    ));
+    assert!(!source.contains("DefinitionName"));
+}
+
+#[test]
+fn test_parse_str_alias_root() {
+    let grammar = GRAMMAR.replace("\"definition\":", "\"_definition\":");
+    let grammar: GrammarJSON = serde_json::from_str(&grammar).unwrap();
+    let config: Config = toml::from_str("language = \"tree_sitter_idl::language\"").unwrap();
+
+    // The root aliases `ScopedName`, whose parser checks the kind of another node.
+    let source = grammar.to_source_with(&config).unwrap();
+    assert!(source.contains("pub type Definition = ScopedName;"), "{source}");
+    assert!(!source.contains("fn parse_str"));
+    assert!(!source.contains("fn parse_tree"));
+}
@@ -0,0 +1,241 @@
+include!("./src/utils.rs");
+
+const CALC: &str = r#"
+    {
+      "name": "calc",
+      "rules": {
+        "expression": {
+          "type": "CHOICE",
+          "members": [
+            { "type": "SYMBOL", "name": "binary_expression" },
+            { "type": "SYMBOL", "name": "list" },
+            { "type": "SYMBOL", "name": "number" }
+          ]
+        },
+        "binary_expression": {
+          "type": "PREC_LEFT",
+          "value": 1,
+          "content": {
+            "type": "SEQ",
+            "members": [
+              { "type": "SYMBOL", "name": "expression" },
+              { "type": "STRING", "value": "+" },
+              { "type": "SYMBOL", "name": "expression" },
+              { "type": "SYMBOL", "name": "number" }
+            ]
+          }
+        },
+        "list": {
+          "type": "SEQ",
+          "members": [
+            { "type": "STRING", "value": "[" },
+            { "type": "REPEAT", "content": { "type": "SYMBOL", "name": "expression" } },
+            { "type": "STRING", "value": "]" }
+          ]
+        },
+        "number": { "type": "PATTERN", "value": "\\d+" }
+      }
+    }
+    "#;
+
+#[test]
+fn test_recursion() {
+    let grammar: GrammarJSON = serde_json::from_str(CALC).unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+
+            #[derive(Debug)]
+            pub struct BinaryExpression {
+                pub expression: Expression,
+                pub plus: tokens::Plus,
+                pub expression_2: Expression,
+                pub number: Number,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub enum Expression {
+                BinaryExpression(Box<BinaryExpression>, Span),
+                List(List, Span),
+                Number(Number, Span),
+            }
+
+            #[derive(Debug)]
+            pub struct List {
+                pub l_bracket: tokens::LBracket,
+                pub expression: Vec<Expression>,
+                pub r_bracket: tokens::RBracket,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Number {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct Plus;
+
+                impl TSParser for Plus {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "+"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("+", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+
+                #[derive(Debug)]
+                pub struct LBracket;
+
+                impl TSParser for LBracket {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "["
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("[", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+
+                #[derive(Debug)]
+                pub struct RBracket;
+
+                impl TSParser for RBracket {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "]"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("]", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_recursion_self() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "chain",
+      "rules": {
+        "chain": {
+          "type": "SEQ",
+          "members": [
+            { "type": "SYMBOL", "name": "link" },
+            { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "chain" }, { "type": "BLANK" }] }
+          ]
+        },
+        "link": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert!(format_types(generated).contains("pub chain: Option<Box<Chain>>,"));
+}
+
+/// `Detach` and `Drop` implementations of the type `name` in `generated`.
+fn drop_impls(generated: TokenStream, name: &str) -> String {
+    let mut file: syn::File = syn::parse2(generated).unwrap();
+    file.items.retain(|item| {
+        let syn::Item::Impl(item) = item else {
+            return false;
+        };
+        let ty = &item.self_ty;
+        let path = item.trait_.as_ref().map(|(_, path, _)| quote!(#path).to_string());
+        quote!(#ty).to_string() == name && matches!(path.as_deref(), Some("Detach" | "Drop"))
+    });
+
+    prettyplease::unparse(&file)
+}
+
+#[test]
+fn test_drop_nested() {
+    let grammar: GrammarJSON = serde_json::from_str(CALC).unwrap();
+    let generated = grammar.to_toke_stream().unwrap();
+
+    assert_eq!(
+        drop_impls(generated.clone(), "BinaryExpression"),
+        prettyplease::unparse(&syn::parse_quote! {
+            impl Detach for BinaryExpression {
+                fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>) {
+                    self.expression.detach(stack);
+                    self.expression_2.detach(stack);
+                }
+
+                fn empty() -> Self {
+                    Self {
+                        expression: <Expression as Detach>::empty(),
+                        plus: tokens::Plus,
+                        expression_2: <Expression as Detach>::empty(),
+                        number: <Number as Detach>::empty(),
+                        span: Span::default(),
+                    }
+                }
+            }
+
+            impl Drop for BinaryExpression {
+                fn drop(&mut self) {
+                    drop_nested(self);
+                }
+            }
+        })
+    );
+    assert_eq!(
+        drop_impls(generated.clone(), "Expression"),
+        prettyplease::unparse(&syn::parse_quote! {
+            impl Detach for Expression {
+                fn detach(&mut self, stack: &mut Vec<Box<dyn Detach>>) {
+                    match self {
+                        Self::BinaryExpression(item0, _) => {
+                            stack.push(Box::new(std::mem::replace(&mut **item0, <BinaryExpression as Detach>::empty())));
+                        }
+                        Self::List(item0, _) => {
+                            item0.detach(stack);
+                        }
+                        Self::Number(..) => {}
+                    }
+                }
+
+                fn empty() -> Self {
+                    Self::Number(<Number as Detach>::empty(), Span::default())
+                }
+            }
+
+            impl Drop for Expression {
+                fn drop(&mut self) {
+                    drop_nested(self);
+                }
+            }
+        })
+    );
+    assert_eq!(
+        drop_impls(generated, "Number"),
+        prettyplease::unparse(&syn::parse_quote! {
+            impl Detach for Number {
+                fn detach(&mut self, _: &mut Vec<Box<dyn Detach>>) {}
+
+                fn empty() -> Self {
+                    Self { value: std::string::String::new(), span: Span::default() }
+                }
+            }
+        })
+    );
+}
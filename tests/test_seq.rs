@@ -50,7 +50,7 @@ fn test_seq() {
             pub enum ScopedName {
                 Identifier(Identifier),
                 Token1(Identifier),
-                Token2(ScopedName, Identifier),
+                Token2(Box<ScopedName>, Identifier),
             }
         }
     ));
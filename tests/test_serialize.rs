@@ -0,0 +1,67 @@
+use sitter_ast::GrammarJSON;
+
+/// Removes the whitespace of `json` outside of strings.
+fn minify(json: &str) -> String {
+    let mut res = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in json.chars() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c.is_whitespace() {
+            continue;
+        } else {
+            in_string = c == '"';
+        }
+        res.push(c);
+    }
+    res
+}
+
+fn round_trip(source: &str) {
+    let grammar: GrammarJSON = serde_json::from_str(source).unwrap();
+    let res = serde_json::to_string_pretty(&grammar).unwrap();
+    assert_eq!(minify(&res), minify(source));
+}
+
+#[test]
+fn test_serialize() {
+    round_trip(include_str!("grammars/json.json"));
+    round_trip(include_str!("grammars/css.json"));
+}
+
+#[test]
+fn test_serialize_rules() {
+    round_trip(
+        r#"
+    {
+      "name": "serialize",
+      "word": "identifier",
+      "rules": {
+        "source": { "type": "REPEAT1", "content": { "type": "SYMBOL", "name": "statement" } },
+        "statement": {
+          "type": "PREC_LEFT",
+          "value": "statement",
+          "content": {
+            "type": "SEQ",
+            "members": [
+              { "type": "FIELD", "name": "name", "content": { "type": "SYMBOL", "name": "identifier" } },
+              { "type": "ALIAS", "content": { "type": "STRING", "value": "=" }, "named": false, "value": ":=" },
+              { "type": "PREC_DYNAMIC", "value": -1, "content": { "type": "BLANK" } },
+              { "type": "IMMEDIATE_TOKEN", "content": { "type": "PATTERN", "value": "[a-z]", "flags": "i" } }
+            ]
+          }
+        },
+        "identifier": { "type": "TOKEN", "content": { "type": "PREC", "value": 1, "content": { "type": "PATTERN", "value": "\\w+" } } }
+      },
+      "extras": [],
+      "conflicts": [["source", "statement"]],
+      "precedences": [[{ "type": "STRING", "value": "statement" }, { "type": "SYMBOL", "name": "source" }]],
+      "externals": [],
+      "inline": [],
+      "supertypes": []
+    }
+    "#,
+    );
+}
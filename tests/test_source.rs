@@ -0,0 +1,35 @@
+use sitter_ast::{Config, GrammarJSON};
+
+#[test]
+fn test_source() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "idl",
+      "rules": {
+        "scoped_name": {
+          "type": "SEQ",
+          "members": [
+            { "type": "STRING", "value": "::" },
+            { "type": "SYMBOL", "name": "identifier" }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let source = grammar.to_source_with(&Config::default()).unwrap();
+    assert!(source.starts_with(&grammar.header()));
+    assert!(source.contains(
+        "#[derive(Debug)]
+pub struct ScopedName {
+    pub colon_colon: tokens::ColonColon,
+    pub identifier: Identifier,
+    pub span: Span,
+}
+"
+    ));
+}
@@ -0,0 +1,45 @@
+use sitter_ast::{Config, GrammarJSON};
+
+#[test]
+fn test_stats() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "stats",
+      "rules": {
+        "expression": {
+          "type": "CHOICE",
+          "members": [{ "type": "SYMBOL", "name": "call" }, { "type": "SYMBOL", "name": "identifier" }]
+        },
+        "call": {
+          "type": "SEQ",
+          "members": [
+            { "type": "SYMBOL", "name": "identifier" },
+            { "type": "STRING", "value": "(" },
+            { "type": "STRING", "value": ")" }
+          ]
+        },
+        "identifier": { "type": "PATTERN", "value": "[a-z]+" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let stats = grammar.stats(&Config::default()).unwrap();
+    assert_eq!(
+        stats.to_string(),
+        "rules: 3
+  CHOICE: 1
+  PATTERN: 1
+  SEQ: 1
+generated types: 8
+  enum: 4
+  struct: 4
+largest rules:
+  call: 4
+  expression: 3
+  identifier: 1
+"
+    );
+}
@@ -2,7 +2,7 @@ include!("./src/utils.rs");
 
 #[test]
 fn test_string() {
-    test_ast(
+    assert!(test_ast(
         "unsigned_tiny_int",
         r#"
     {
@@ -12,7 +12,9 @@ fn test_string() {
     "#,
         quote! {
             #[derive(Debug)]
-            pub struct UnsignedTinyInt;
+            pub struct UnsignedTinyInt {
+                pub span: Span,
+            }
         },
-    );
+    ));
 }
@@ -0,0 +1,235 @@
+include!("./src/utils.rs");
+
+#[test]
+fn test_token() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "token",
+      "rules": {
+        "number_literal": {
+          "type": "TOKEN",
+          "content": {
+            "type": "SEQ",
+            "members": [
+              { "type": "CHOICE", "members": [{ "type": "STRING", "value": "-" }, { "type": "BLANK" }] },
+              { "type": "PATTERN", "value": "\\d+" },
+              {
+                "type": "IMMEDIATE_TOKEN",
+                "content": { "type": "SEQ", "members": [{ "type": "STRING", "value": "." }, { "type": "PATTERN", "value": "\\d+" }] }
+              }
+            ]
+          }
+        }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct NumberLiteral {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+            }
+        })
+    );
+}
+
+#[test]
+fn test_token_span() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "token",
+      "rules": {
+        "call": {
+          "type": "SEQ",
+          "members": [
+            { "type": "SYMBOL", "name": "identifier" },
+            { "type": "IMMEDIATE_TOKEN", "content": { "type": "STRING", "value": "(" } },
+            { "type": "STRING", "value": ")" }
+          ]
+        },
+        "identifier": { "type": "TOKEN", "content": { "type": "PATTERN", "value": "[a-z]+" } }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct Call {
+                pub identifier: Identifier,
+                pub l_paren: tokens::LParen,
+                pub r_paren: tokens::RParen,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Identifier {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct LParen;
+
+                impl TSParser for LParen {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "("
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("(", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+
+                #[derive(Debug)]
+                pub struct RParen;
+
+                impl TSParser for RParen {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == ")"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf(")", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_token_out_of_tree() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r#"
+    {
+      "name": "token",
+      "rules": {
+        "call": {
+          "type": "SEQ",
+          "members": [
+            { "type": "SYMBOL", "name": "identifier" },
+            { "type": "TOKEN", "content": { "type": "PATTERN", "value": "\\s*:" } },
+            { "type": "REPEAT", "content": { "type": "SYMBOL", "name": "_space" } },
+            { "type": "CHOICE", "members": [{ "type": "SYMBOL", "name": "identifier" }, { "type": "PATTERN", "value": "_" }] }
+          ]
+        },
+        "identifier": { "type": "TOKEN", "content": { "type": "PATTERN", "value": "[a-z]+" } },
+        "_space": { "type": "PATTERN", "value": "\\s" }
+      }
+    }
+    "#,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub enum CallToken3 {
+                Identifier(Identifier),
+                Token1,
+            }
+
+            #[derive(Debug)]
+            pub struct Call {
+                pub identifier: Identifier,
+                pub call_token_3: CallToken3,
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Identifier {
+                pub value: std::string::String,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+            }
+        })
+    );
+}
+
+#[test]
+fn test_token_nested() {
+    let grammar: GrammarJSON = serde_json::from_str(
+        r##"
+    {
+      "name": "token",
+      "rules": {
+        "number": {
+          "type": "SEQ",
+          "members": [
+            { "type": "STRING", "value": "#" },
+            { "type": "REPEAT1", "content": { "type": "PATTERN", "value": "\\d" } },
+            { "type": "FIELD", "name": "suffix", "content": { "type": "TOKEN", "content": { "type": "PATTERN", "value": "[a-z]+" } } }
+          ]
+        },
+        "digits": { "type": "REPEAT1", "content": { "type": "PATTERN", "value": "\\d" } }
+      }
+    }
+    "##,
+    )
+    .unwrap();
+
+    let generated = grammar.to_toke_stream().unwrap();
+    assert_eq!(
+        format_types(generated.clone()),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct Digits {
+                pub span: Span,
+            }
+
+            #[derive(Debug)]
+            pub struct Number {
+                pub hash: tokens::Hash,
+                pub span: Span,
+            }
+
+            pub mod tokens {
+                use super::*;
+
+                #[derive(Debug)]
+                pub struct Hash;
+
+                impl TSParser for Hash {
+                    fn starts_with(node: TSNode<'_>) -> bool {
+                        node.kind() == "#"
+                    }
+
+                    fn parse_children(children: &mut Children<'_, '_>, _source: &[u8]) -> ParseResult<Self> {
+                        children.leaf("#", Self::starts_with)?;
+                        Ok(Self)
+                    }
+                }
+            }
+        })
+    );
+    // Nested tokens are no nodes of their own, only the ones of rules are checked.
+    let generated = generated.to_string();
+    assert!(!generated.contains("NumberToken") && !generated.contains("DigitsToken"));
+}
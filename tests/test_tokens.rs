@@ -0,0 +1,77 @@
+include!("./src/utils.rs");
+
+#[test]
+fn test_tokens() {
+    let mut ctx = Context::new();
+    let qualified = generate(
+        &mut ctx,
+        "qualified_name",
+        r#"
+    {
+      "type": "SEQ",
+      "members": [
+        {
+          "type": "SYMBOL",
+          "name": "identifier"
+        },
+        {
+          "type": "STRING",
+          "value": "::"
+        },
+        {
+          "type": "SYMBOL",
+          "name": "identifier"
+        }
+      ]
+    }
+    "#,
+    );
+    let global = generate(
+        &mut ctx,
+        "global_name",
+        r#"
+    {
+      "type": "SEQ",
+      "members": [
+        {
+          "type": "STRING",
+          "value": "::"
+        },
+        {
+          "type": "SYMBOL",
+          "name": "identifier"
+        }
+      ]
+    }
+    "#,
+    );
+
+    assert_eq!(
+        format_types(qualified),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct QualifiedName {
+                pub identifier: Identifier,
+                pub colon_colon: tokens::ColonColon,
+                pub identifier_2: Identifier,
+            }
+        })
+    );
+    assert_eq!(
+        format_types(global),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct GlobalName {
+                pub colon_colon: tokens::ColonColon,
+                pub identifier: Identifier,
+            }
+        })
+    );
+    assert_eq!(
+        format_types(ctx.tokens().unwrap()),
+        format_types(quote! {
+            #[derive(Debug)]
+            pub struct ColonColon;
+        })
+    );
+}